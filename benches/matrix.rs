@@ -0,0 +1,136 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use linalg::Matrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Fixed seed so every run benchmarks the same matrices.
+const SEED: u64 = 0x5eed;
+
+fn random_matrix(rng: &mut StdRng, rows: usize, cols: usize) -> Matrix {
+    Matrix {
+        rows,
+        cols,
+        data: (0..rows * cols)
+            .map(|_| rng.gen_range(-10.0..10.0))
+            .collect(),
+    }
+}
+
+fn bench_dot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dot");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [16, 64, 256, 512] {
+        let a = random_matrix(&mut rng, n, n);
+        let b = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter_batched(
+                || b.clone(),
+                |b| black_box(a.dot(&b)),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_det(c: &mut Criterion) {
+    let mut group = c.benchmark_group("det");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [3, 5, 10, 20, 100] {
+        let a = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter(|| black_box(a.det()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_inverse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("inverse");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [3, 5, 10, 20, 100] {
+        let a = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter(|| black_box(a.inverse()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_rref(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rref");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [3, 10, 50, 100] {
+        let augmented = random_matrix(&mut rng, n, n + 1);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter_batched(
+                || augmented.clone(),
+                |mut m| {
+                    m.rref();
+                    black_box(m)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_svd(c: &mut Criterion) {
+    let mut group = c.benchmark_group("svd");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [10, 50, 100] {
+        let a = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter(|| black_box(a.svd()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_qr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("col_pivot_qr");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [10, 50, 100] {
+        let a = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |bench, _| {
+            bench.iter(|| black_box(a.col_pivot_qr()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for n in [64, 256, 1024] {
+        let a = random_matrix(&mut rng, n, n);
+        group.bench_with_input(BenchmarkId::new("serial", n), &n, |bench, _| {
+            bench.iter(|| black_box(a.apply(f64::sin)))
+        });
+        #[cfg(feature = "rayon")]
+        group.bench_with_input(BenchmarkId::new("parallel", n), &n, |bench, _| {
+            bench.iter_batched(
+                || a.clone(),
+                |mut m| {
+                    m.apply_parallel(f64::sin);
+                    black_box(m)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_dot,
+    bench_det,
+    bench_inverse,
+    bench_rref,
+    bench_svd,
+    bench_qr,
+    bench_apply
+);
+criterion_main!(benches);
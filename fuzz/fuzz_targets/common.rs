@@ -0,0 +1,44 @@
+use std::panic;
+
+use linalg::{Matrix, MatrixError};
+
+/// Runs `f`, turning a panic into its message. libfuzzer aborts from its panic
+/// hook, so the hook is silenced for the duration of the call and restored
+/// before the caller decides whether the panic was an expected one.
+pub fn catch_panic<T>(f: impl FnOnce() -> T + panic::UnwindSafe) -> Result<T, String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(f);
+    panic::set_hook(hook);
+    result.map_err(|payload| match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(_) => "non-string panic payload".to_string(),
+    })
+}
+
+/// Checks that a panicking constructor agrees with its fallible counterpart:
+/// the same matrix on success, and a panic carrying the documented error's
+/// message on failure.
+pub fn check_wrapper(panicking: Result<Matrix, String>, fallible: Result<Matrix, MatrixError>) {
+    match (panicking, fallible) {
+        (Ok(m), Ok(expected)) => {
+            assert_eq!((m.rows, m.cols), (expected.rows, expected.cols));
+            assert!(m
+                .data
+                .iter()
+                .zip(&expected.data)
+                .all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+        (Err(message), Err(e)) => {
+            assert!(matches!(
+                e,
+                MatrixError::ParseError(_)
+                    | MatrixError::DimensionMismatch(_)
+                    | MatrixError::Io { .. }
+            ));
+            assert_eq!(message, e.to_string());
+        }
+        (Ok(_), Err(e)) => panic!("wrapper succeeded but the fallible parser returned {e}"),
+        (Err(message), Ok(_)) => panic!("wrapper panicked with {message:?} on valid input"),
+    }
+}
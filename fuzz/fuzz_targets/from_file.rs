@@ -0,0 +1,17 @@
+#![no_main]
+
+use std::io::Write;
+
+use libfuzzer_sys::fuzz_target;
+use linalg::Matrix;
+
+fuzz_target!(|data: &[u8]| {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+
+    // Unreadable, non-UTF-8, non-numeric and ragged input must all come back
+    // as errors rather than panics.
+    if let Ok(m) = Matrix::try_from_file(file.path().to_str().unwrap()) {
+        assert_eq!(m.data.len(), m.rows * m.cols);
+    }
+});
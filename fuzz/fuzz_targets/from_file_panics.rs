@@ -0,0 +1,19 @@
+#![no_main]
+
+mod common;
+
+use std::io::Write;
+
+use libfuzzer_sys::fuzz_target;
+use linalg::Matrix;
+
+// `Matrix::from_file` may only panic with the message of the error that
+// `try_from_file` reports for the same file.
+fuzz_target!(|data: &[u8]| {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let panicking = common::catch_panic(|| Matrix::from_file(path));
+    common::check_wrapper(panicking, Matrix::try_from_file(path));
+});
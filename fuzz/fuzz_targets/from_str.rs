@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linalg::Matrix;
+
+// Fuzzes the `FromStr` impl. `Matrix::from_string` is a panicking wrapper over
+// it and is deliberately not exercised here.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok(m) = input.parse::<Matrix>() {
+            assert_eq!(m.data.len(), m.rows * m.cols);
+        }
+    }
+});
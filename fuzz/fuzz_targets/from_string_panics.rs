@@ -0,0 +1,15 @@
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+use linalg::Matrix;
+
+// `Matrix::from_string` may only panic with the message of the error that
+// `FromStr` reports for the same input.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let panicking = common::catch_panic(|| Matrix::from_string(input));
+        common::check_wrapper(panicking, input.parse::<Matrix>());
+    }
+});
@@ -0,0 +1,85 @@
+#ifndef LINALG_H
+#define LINALG_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stddef.h>
+
+/* Opaque matrix handle. Release every handle with `matrix_free`. */
+typedef struct MatrixHandle MatrixHandle;
+
+// Creates a `rows x cols` matrix of zeros. Returns null if `rows * cols`
+// overflows or exceeds the largest possible allocation.
+MatrixHandle *matrix_new(size_t rows, size_t cols);
+
+// Creates a matrix by copying `rows * cols` row-major values from `data`.
+// Returns null if `data` is null or `rows * cols` overflows.
+//
+// # Safety
+//
+// `data` must point to at least `rows * cols` readable `double`s.
+MatrixHandle *matrix_from_array(size_t rows, size_t cols, const double *data);
+
+// Releases a handle. Passing null is a no-op.
+//
+// # Safety
+//
+// `m` must be null or a handle returned by this API that has not been freed.
+void matrix_free(MatrixHandle *m);
+
+// Number of rows, or 0 for a null handle.
+//
+// # Safety
+//
+// `m` must be null or a live handle.
+size_t matrix_rows(const MatrixHandle *m);
+
+// Number of columns, or 0 for a null handle.
+//
+// # Safety
+//
+// `m` must be null or a live handle.
+size_t matrix_cols(const MatrixHandle *m);
+
+// Pointer to the `rows * cols` row-major values, or null for a null handle.
+// The pointer is borrowed from `m` and is invalidated by `matrix_free` and
+// `matrix_rref`.
+//
+// # Safety
+//
+// `m` must be null or a live handle.
+const double *matrix_data(const MatrixHandle *m);
+
+// Returns the new matrix `a * b`, or null if either handle is null or the
+// inner dimensions differ.
+//
+// # Safety
+//
+// `a` and `b` must be null or live handles.
+MatrixHandle *matrix_dot(const MatrixHandle *a, const MatrixHandle *b);
+
+// Writes the determinant of `m` to `out`. Returns 0 on success and -1 if a
+// pointer is null or `m` is not square.
+//
+// # Safety
+//
+// `m` must be null or a live handle, and `out` null or valid for writes.
+int matrix_det(const MatrixHandle *m, double *out);
+
+// Returns the new matrix `m^-1`, or null if `m` is null, not square or
+// singular.
+//
+// # Safety
+//
+// `m` must be null or a live handle.
+MatrixHandle *matrix_inverse(const MatrixHandle *m);
+
+// Reduces `m` to reduced row echelon form in place. Returns 0 on success and
+// -1 if `m` is null or empty.
+//
+// # Safety
+//
+// `m` must be null or a live handle.
+int matrix_rref(MatrixHandle *m);
+
+#endif  /* LINALG_H */
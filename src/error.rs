@@ -0,0 +1,116 @@
+use std::fmt::Display;
+use std::num::ParseFloatError;
+
+/// Errors returned by the fallible `Matrix` operations.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MatrixError {
+    /// Operand shapes are incompatible. The message names the shapes involved.
+    DimensionMismatch(String),
+    /// The operation is only defined for square matrices.
+    NotSquare { rows: usize, cols: usize },
+    /// The matrix is singular, so the operation has no solution.
+    Singular,
+    /// An argument is outside the range the operation accepts.
+    InvalidParameter(String),
+    /// An entry could not be parsed as a number.
+    ParseError(ParseFloatError),
+    /// Serialized input does not follow the expected layout.
+    InvalidFormat(String),
+    /// A row or column index is past the end of the matrix.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// An iterative method did not reach its tolerance.
+    NoConvergence { iterations: usize },
+    /// A linear system has no solution.
+    Inconsistent,
+    /// Reading or writing a file failed. Holds the `std::io::Error` kind and message.
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+impl Display for MatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch(msg) => write!(f, "Dimensions not matched. {msg}"),
+            MatrixError::NotSquare { rows, cols } => {
+                write!(f, "Not a square matrix. Input matrix was {rows} by {cols}.")
+            }
+            MatrixError::Singular => write!(f, "Matrix is singular."),
+            MatrixError::InvalidParameter(msg) => write!(f, "Invalid parameter. {msg}"),
+            MatrixError::ParseError(e) => write!(f, "Could not parse entry: {e}."),
+            MatrixError::InvalidFormat(msg) => write!(f, "Invalid format. {msg}"),
+            MatrixError::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {index} is out of bounds for length {len}.")
+            }
+            MatrixError::NoConvergence { iterations } => {
+                write!(f, "Did not converge after {iterations} iterations.")
+            }
+            MatrixError::Inconsistent => write!(f, "System is inconsistent."),
+            MatrixError::Io { message, .. } => write!(f, "I/O error: {message}."),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MatrixError::ParseError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseFloatError> for MatrixError {
+    fn from(e: ParseFloatError) -> Self {
+        MatrixError::ParseError(e)
+    }
+}
+
+impl From<std::io::Error> for MatrixError {
+    fn from(e: std::io::Error) -> Self {
+        MatrixError::Io {
+            kind: e.kind(),
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+    use std::error::Error;
+
+    #[test]
+    fn test_display() {
+        let e = MatrixError::NotSquare { rows: 2, cols: 3 };
+
+        assert_eq!(
+            "Not a square matrix. Input matrix was 2 by 3.",
+            e.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_error_source() {
+        let e = "1 x ; 3 4".parse::<Matrix>().unwrap_err();
+
+        assert!(matches!(e, MatrixError::ParseError(_)));
+        assert!(e.source().is_some());
+    }
+
+    #[test]
+    fn test_question_mark() {
+        fn boxed() -> Result<Matrix, Box<dyn Error>> {
+            Ok("1 2 ; 3".parse::<Matrix>()?)
+        }
+        fn with_anyhow() -> anyhow::Result<Matrix> {
+            Ok("1 2 ; 3 4".parse::<Matrix>()?)
+        }
+
+        assert!(boxed().is_err());
+        assert!(with_anyhow().is_ok());
+    }
+}
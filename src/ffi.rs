@@ -0,0 +1,239 @@
+//! C API, enabled with the `c-api` feature. Build the shared library with
+//! `cargo rustc --release --lib --features c-api --crate-type cdylib`. The
+//! header `include/linalg.h` is generated with
+//! `cbindgen --config cbindgen.toml --output include/linalg.h`.
+//!
+//! Matrices cross the boundary as opaque `MatrixHandle` pointers. Every handle
+//! returned by this module is owned by the caller and must be released with
+//! exactly one call to `matrix_free`. Functions that can fail return a null
+//! handle or a nonzero status. Every entry point catches panics, so a Rust
+//! panic never unwinds into C; it is reported the same way as a failure.
+
+use std::os::raw::c_int;
+use std::panic::{self, UnwindSafe};
+use std::ptr;
+
+use crate::lu::Lu;
+use crate::Matrix;
+
+/// Opaque matrix handle. Only ever used behind a pointer.
+///
+/// cbindgen:no-export
+#[repr(C)]
+pub struct MatrixHandle {
+    _private: [u8; 0],
+}
+
+fn into_handle(m: Matrix) -> *mut MatrixHandle {
+    Box::into_raw(Box::new(m)) as *mut MatrixHandle
+}
+
+/// Runs `f`, returning `fallback` instead if it panics.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T + UnwindSafe) -> T {
+    panic::catch_unwind(f).unwrap_or(fallback)
+}
+
+unsafe fn as_matrix<'a>(m: *const MatrixHandle) -> Option<&'a Matrix> {
+    (m as *const Matrix).as_ref()
+}
+
+/// Creates a `rows x cols` matrix of zeros. Returns null if `rows * cols`
+/// overflows or exceeds the largest possible allocation.
+#[no_mangle]
+pub extern "C" fn matrix_new(rows: usize, cols: usize) -> *mut MatrixHandle {
+    guard(ptr::null_mut(), || match rows.checked_mul(cols) {
+        Some(_) => into_handle(Matrix::new(rows, cols)),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Creates a matrix by copying `rows * cols` row-major values from `data`.
+/// Returns null if `data` is null or `rows * cols` overflows.
+///
+/// # Safety
+///
+/// `data` must point to at least `rows * cols` readable `double`s.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_from_array(
+    rows: usize,
+    cols: usize,
+    data: *const f64,
+) -> *mut MatrixHandle {
+    guard(ptr::null_mut(), || match rows.checked_mul(cols) {
+        Some(len) if !data.is_null() => into_handle(Matrix::from_raw_ptr(rows, cols, data, len)),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// Releases a handle. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `m` must be null or a handle returned by this API that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_free(m: *mut MatrixHandle) {
+    guard((), || {
+        if !m.is_null() {
+            drop(Box::from_raw(m as *mut Matrix));
+        }
+    })
+}
+
+/// Number of rows, or 0 for a null handle.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rows(m: *const MatrixHandle) -> usize {
+    guard(0, || as_matrix(m).map_or(0, |m| m.rows))
+}
+
+/// Number of columns, or 0 for a null handle.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_cols(m: *const MatrixHandle) -> usize {
+    guard(0, || as_matrix(m).map_or(0, |m| m.cols))
+}
+
+/// Pointer to the `rows * cols` row-major values, or null for a null handle.
+/// The pointer is borrowed from `m` and is invalidated by `matrix_free` and
+/// `matrix_rref`.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_data(m: *const MatrixHandle) -> *const f64 {
+    guard(ptr::null(), || {
+        as_matrix(m).map_or(ptr::null(), |m| m.data.as_ptr())
+    })
+}
+
+/// Returns the new matrix `a * b`, or null if either handle is null or the
+/// inner dimensions differ.
+///
+/// # Safety
+///
+/// `a` and `b` must be null or live handles.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_dot(
+    a: *const MatrixHandle,
+    b: *const MatrixHandle,
+) -> *mut MatrixHandle {
+    guard(ptr::null_mut(), || match (as_matrix(a), as_matrix(b)) {
+        (Some(a), Some(b)) if a.cols == b.rows => into_handle(a.dot(b)),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// Writes the determinant of `m` to `out`. Returns 0 on success and -1 if a
+/// pointer is null or `m` is not square.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle, and `out` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_det(m: *const MatrixHandle, out: *mut f64) -> c_int {
+    guard(-1, || match as_matrix(m) {
+        Some(m) if !out.is_null() && m.rows == m.cols => {
+            *out = m.det();
+            0
+        }
+        _ => -1,
+    })
+}
+
+/// Returns the new matrix `m^-1`, or null if `m` is null, not square or
+/// singular.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_inverse(m: *const MatrixHandle) -> *mut MatrixHandle {
+    guard(ptr::null_mut(), || match as_matrix(m) {
+        Some(m) if m.rows == m.cols => Lu::new(m)
+            .and_then(|lu| lu.solve(&Matrix::eye(m.rows)))
+            .map_or(ptr::null_mut(), into_handle),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// Reduces `m` to reduced row echelon form in place. Returns 0 on success and
+/// -1 if `m` is null or empty.
+///
+/// # Safety
+///
+/// `m` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rref(m: *mut MatrixHandle) -> c_int {
+    guard(-1, || match (m as *mut Matrix).as_mut() {
+        Some(m) if m.rows > 0 && m.cols > 0 => {
+            m.rref();
+            0
+        }
+        _ => -1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        unsafe {
+            let values = [4.0, 7.0, 2.0, 6.0];
+            let a = matrix_from_array(2, 2, values.as_ptr());
+            let inv = matrix_inverse(a);
+            let product = matrix_dot(a, inv);
+
+            let mut det = 0.0;
+            assert_eq!(matrix_det(a, &mut det), 0);
+            assert!((det - 10.0).abs() < 1e-12);
+
+            assert_eq!((matrix_rows(product), matrix_cols(product)), (2, 2));
+            let data = std::slice::from_raw_parts(matrix_data(product), 4);
+            assert!(data
+                .iter()
+                .zip([1.0, 0.0, 0.0, 1.0])
+                .all(|(a, b)| (a - b).abs() < 1e-12));
+
+            matrix_free(product);
+            matrix_free(inv);
+            matrix_free(a);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            let a = matrix_new(2, 3);
+            let mut det = 0.0;
+
+            assert!(matrix_dot(a, a).is_null());
+            assert!(matrix_inverse(a).is_null());
+            let singular = [1.0, 2.0, 2.0, 4.0];
+            let s = matrix_from_array(2, 2, singular.as_ptr());
+            assert!(matrix_inverse(s).is_null());
+            matrix_free(s);
+            assert_eq!(matrix_det(a, &mut det), -1);
+            assert!(matrix_from_array(1, 1, ptr::null()).is_null());
+            assert_eq!(matrix_rref(ptr::null_mut()), -1);
+
+            // Overflowing sizes, and sizes past the largest possible
+            // allocation, return null instead of panicking into the caller.
+            assert!(matrix_new(usize::MAX, 2).is_null());
+            assert!(matrix_new(1 << 61, 1).is_null());
+            let values = [1.0];
+            assert!(matrix_from_array(usize::MAX, 2, values.as_ptr()).is_null());
+
+            matrix_free(a);
+            matrix_free(ptr::null_mut());
+        }
+    }
+}
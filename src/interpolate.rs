@@ -0,0 +1,86 @@
+//! Polynomial interpolation through scattered points.
+
+use crate::MatrixError;
+
+/// Evaluates the polynomial through `(points[i], values[i])` at each query
+/// point, using the second (true) barycentric form. This avoids forming and
+/// solving the ill-conditioned Vandermonde system. `points` must be distinct.
+///
+/// Returns `DimensionMismatch` if `points` and `values` differ in length and
+/// `InvalidParameter` if there are no points or two of them coincide, where
+/// the weights would divide by zero.
+pub fn barycentric_interpolate(
+    points: &[f64],
+    values: &[f64],
+    query: &[f64],
+) -> Result<Vec<f64>, MatrixError> {
+    if points.len() != values.len() {
+        return Err(MatrixError::DimensionMismatch(format!(
+            "Got {} points but {} values.",
+            points.len(),
+            values.len()
+        )));
+    }
+    if points.is_empty() {
+        return Err(MatrixError::InvalidParameter(
+            "At least one interpolation point is required.".to_string(),
+        ));
+    }
+    let mut weights = vec![1.0; points.len()];
+    for (j, w) in weights.iter_mut().enumerate() {
+        for (k, x) in points.iter().enumerate() {
+            if k != j {
+                let diff = points[j] - x;
+                if diff == 0.0 {
+                    return Err(MatrixError::InvalidParameter(format!(
+                        "Interpolation point {} appears more than once.",
+                        points[j]
+                    )));
+                }
+                *w /= diff;
+            }
+        }
+    }
+
+    Ok(query
+        .iter()
+        .map(|&x| {
+            if let Some(i) = points.iter().position(|&p| p == x) {
+                return values[i];
+            }
+            let (mut num, mut den) = (0.0, 0.0);
+            for ((p, v), w) in points.iter().zip(values).zip(&weights) {
+                let t = w / (x - p);
+                num += t * v;
+                den += t;
+            }
+            num / den
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_barycentric_interpolate() {
+        // p(x) = x^3 - 2x + 1 is reproduced exactly from four points.
+        let p = |x: f64| x * x * x - 2.0 * x + 1.0;
+        let points = [-1.0, 0.0, 1.5, 3.0];
+        let values: Vec<f64> = points.iter().map(|&x| p(x)).collect();
+        let query = [-2.0, 0.0, 0.5, 2.0];
+        let got = barycentric_interpolate(&points, &values, &query).unwrap();
+        for (x, y) in query.iter().zip(got) {
+            assert!((y - p(*x)).abs() < 1e-10);
+        }
+        assert!(matches!(
+            barycentric_interpolate(&[1.0, 1.0], &[0.0, 1.0], &[0.5]),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            barycentric_interpolate(&[1.0], &[], &[0.5]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+}
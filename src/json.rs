@@ -0,0 +1,203 @@
+//! Dependency-free JSON (de)serialization of the form
+//! `{"rows":2,"cols":3,"data":[[1,2,3],[4,5,6]]}`.
+
+use crate::{Matrix, MatrixError};
+
+impl Matrix {
+    /// Serializes to `{"rows":r,"cols":c,"data":[[...],...]}`.
+    ///
+    /// Non-finite entries are written as `NaN`, `inf` and `-inf`, which
+    /// `from_json` accepts but strict JSON parsers will reject.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = (0..self.rows)
+            .map(|r| {
+                let entries: Vec<String> = self[r].iter().map(|elem| elem.to_string()).collect();
+                format!("[{}]", entries.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"rows\":{},\"cols\":{},\"data\":[{}]}}",
+            self.rows,
+            self.cols,
+            rows.join(",")
+        )
+    }
+
+    /// Parses the format written by `to_json`. Keys may appear in any order and
+    /// whitespace between tokens is ignored.
+    pub fn from_json(s: &str) -> Result<Self, MatrixError> {
+        let mut p = Parser { input: s, pos: 0 };
+        let mut rows = None;
+        let mut cols = None;
+        let mut data: Option<Vec<Vec<f64>>> = None;
+
+        p.expect('{')?;
+        loop {
+            let key = p.string()?;
+            p.expect(':')?;
+            match key {
+                "rows" => rows = Some(p.size()?),
+                "cols" => cols = Some(p.size()?),
+                "data" => data = Some(p.list(|p| p.list(Parser::number))?),
+                _ => return Err(p.error(&format!("unknown key \"{key}\""))),
+            }
+            if !p.eat(',') {
+                break;
+            }
+        }
+        p.expect('}')?;
+        p.skip_whitespace();
+        if p.pos != p.input.len() {
+            return Err(p.error("trailing characters"));
+        }
+
+        let (rows, cols, data) = match (rows, cols, data) {
+            (Some(rows), Some(cols), Some(data)) => (rows, cols, data),
+            _ => {
+                return Err(MatrixError::InvalidFormat(
+                    "Expected \"rows\", \"cols\" and \"data\" keys.".to_string(),
+                ))
+            }
+        };
+        if data.len() != rows || data.iter().any(|row| row.len() != cols) {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Data does not form a {rows} by {cols} matrix."
+            )));
+        }
+
+        Ok(Self {
+            rows,
+            cols,
+            data: data.into_iter().flatten().collect(),
+        })
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> MatrixError {
+        MatrixError::InvalidFormat(format!("JSON {msg} at byte {}.", self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), MatrixError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a str, MatrixError> {
+        self.expect('"')?;
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find('"')
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += end + 1;
+        Ok(&rest[..end])
+    }
+
+    fn token(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| c == ',' || c == ']' || c == '}' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn number(&mut self) -> Result<f64, MatrixError> {
+        Ok(self.token().parse::<f64>()?)
+    }
+
+    fn size(&mut self) -> Result<usize, MatrixError> {
+        let token = self.token();
+        token
+            .parse::<usize>()
+            .map_err(|_| self.error(&format!("expected a size, found \"{token}\"")))
+    }
+
+    fn list<T>(
+        &mut self,
+        item: impl Fn(&mut Self) -> Result<T, MatrixError>,
+    ) -> Result<Vec<T>, MatrixError> {
+        let mut items = Vec::new();
+        self.expect('[')?;
+        if self.eat(']') {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let m = Matrix::from_string("1 2 3 ; 4 5.5 6");
+
+        assert_eq!(
+            m.to_json(),
+            r#"{"rows":2,"cols":3,"data":[[1,2,3],[4,5.5,6]]}"#
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let m =
+            Matrix::from_json(r#" { "data" : [ [1, -2e3], [0.5, 4] ], "cols": 2, "rows": 2 } "#)
+                .unwrap();
+
+        assert_eq!(m, Matrix::from_string("1 -2000 ; 0.5 4"));
+        assert_eq!(Matrix::from_json(&m.to_json()).unwrap(), m);
+        assert_eq!(
+            Matrix::from_json(&Matrix::new(0, 3).to_json()).unwrap(),
+            Matrix::new(0, 3)
+        );
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        assert!(matches!(
+            Matrix::from_json(r#"{"rows":1,"cols":2,"data":[[1,2,3]]}"#),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            Matrix::from_json(r#"{"rows":1,"cols":1,"data":[[x]]}"#),
+            Err(MatrixError::ParseError(_))
+        ));
+        assert!(matches!(
+            Matrix::from_json(r#"{"rows":1,"cols":1"#),
+            Err(MatrixError::InvalidFormat(_))
+        ));
+    }
+}
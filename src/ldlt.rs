@@ -0,0 +1,154 @@
+//! Bunch-Kaufman `LDL^T` factorization of symmetric indefinite matrices.
+
+use crate::{Matrix, MatrixError};
+
+impl Matrix {
+    /// Bunch-Kaufman factorization `P A P^T = L D L^T` of a symmetric matrix.
+    ///
+    /// Returns the unit lower triangular `L`, the block diagonal `D` (1x1 and
+    /// symmetric 2x2 blocks) and the permutation, where `perm[i]` is the row of
+    /// `A` moved to row `i`. Unlike Cholesky it does not need `A` to be positive
+    /// definite, and the pivoting keeps the entries of `L` bounded.
+    pub fn ldlt(&self) -> Result<(Self, Self, Vec<usize>), MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let max = self.max_abs_element();
+        if !self.is_symmetric(f64::EPSILON * n as f64 * max) {
+            return Err(MatrixError::InvalidParameter(
+                "LDL^T requires a symmetric matrix.".to_string(),
+            ));
+        }
+
+        // Growth bound from Bunch and Kaufman (1977).
+        let alpha = (1.0 + 17f64.sqrt()) / 8.0;
+        let mut a = self.clone();
+        let mut l = Self::eye(n);
+        let mut d = Self::new(n, n);
+        let mut perm: Vec<usize> = (0..n).collect();
+
+        let mut k = 0;
+        while k < n {
+            let (r, lambda) = (k + 1..n)
+                .map(|i| (i, a[i][k].abs()))
+                .fold(
+                    (k, 0.0),
+                    |best, cur| if cur.1 > best.1 { cur } else { best },
+                );
+
+            let mut two_by_two = false;
+            if a[k][k].abs() < alpha * lambda {
+                let sigma = (k..n)
+                    .filter(|&j| j != r)
+                    .fold(0.0_f64, |acc, j| acc.max(a[r][j].abs()));
+                if a[k][k].abs() * sigma >= alpha * lambda * lambda {
+                    // Keep the 1x1 pivot at k.
+                } else if a[r][r].abs() >= alpha * sigma {
+                    Self::symmetric_swap(&mut a, &mut l, &mut perm, k, r);
+                } else {
+                    Self::symmetric_swap(&mut a, &mut l, &mut perm, k + 1, r);
+                    two_by_two = true;
+                }
+            }
+
+            if two_by_two {
+                let (p, q, s) = (a[k][k], a[k + 1][k], a[k + 1][k + 1]);
+                let det = p * s - q * q;
+                d[k][k] = p;
+                d[k + 1][k] = q;
+                d[k][k + 1] = q;
+                d[k + 1][k + 1] = s;
+                for i in k + 2..n {
+                    let (x, y) = (a[i][k], a[i][k + 1]);
+                    l[i][k] = (x * s - y * q) / det;
+                    l[i][k + 1] = (y * p - x * q) / det;
+                }
+                for i in k + 2..n {
+                    for j in k + 2..n {
+                        a[i][j] -= l[i][k] * a[j][k] + l[i][k + 1] * a[j][k + 1];
+                    }
+                }
+                k += 2;
+            } else {
+                let pivot = a[k][k];
+                d[k][k] = pivot;
+                // An all-zero column leaves a zero pivot and nothing to eliminate.
+                if pivot != 0.0 {
+                    for i in k + 1..n {
+                        l[i][k] = a[i][k] / pivot;
+                    }
+                    for i in k + 1..n {
+                        for j in k + 1..n {
+                            a[i][j] -= l[i][k] * a[j][k];
+                        }
+                    }
+                }
+                k += 1;
+            }
+        }
+        Ok((l, d, perm))
+    }
+
+    /// Swaps rows and columns `i` and `j` of the active matrix, together with
+    /// the already computed rows of `L` and the permutation.
+    fn symmetric_swap(a: &mut Self, l: &mut Self, perm: &mut [usize], i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        a.swap_rows(i, j);
+        for r in 0..a.rows {
+            a.data.swap(r * a.cols + i, r * a.cols + j);
+        }
+        for c in 0..i.min(j) {
+            l.data.swap(i * l.cols + c, j * l.cols + c);
+        }
+        perm.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(a: &Matrix) -> Matrix {
+        let (l, d, perm) = a.ldlt().unwrap();
+        let n = a.rows;
+        let mut pap = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                pap[i][j] = a[perm[i]][perm[j]];
+            }
+            assert_eq!(l[i][i], 1.0);
+            assert!(l[i][i + 1..].iter().all(|x| *x == 0.0));
+        }
+        let ldl = l.dot(&d).dot(&l.transpose());
+        assert!(pap
+            .data
+            .iter()
+            .zip(&ldl.data)
+            .all(|(x, y)| (x - y).abs() < 1e-12));
+        d
+    }
+
+    #[test]
+    fn test_ldlt() {
+        // A zero diagonal forces a 2x2 pivot.
+        let d = check(&Matrix::from_string("0 1; 1 0"));
+        assert_eq!(d, Matrix::from_string("0 1; 1 0"));
+
+        // Saddle-point (KKT) matrix.
+        check(&Matrix::from_string("4 1 0 1; 1 3 1 0; 0 1 0 0; 1 0 0 0"));
+        check(&Matrix::from_string("1 2 3; 2 -4 5; 3 5 0.5"));
+        check(&Matrix::from_string("2 0; 0 0"));
+        // Zero diagonal throughout: 2x2 pivots with rows left to eliminate.
+        let d = check(&Matrix::from_string(
+            "0 1 2 3 4; 1 0 5 6 7; 2 5 0 8 9; 3 6 8 0 1; 4 7 9 1 0",
+        ));
+        assert!((0..4).any(|i| d[i + 1][i] != 0.0));
+
+        assert!(matches!(
+            Matrix::from_string("1 2; 3 4").ldlt(),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(Matrix::new(2, 3).ldlt().is_err());
+    }
+}
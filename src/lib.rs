@@ -1,7 +1,36 @@
+use rand::Rng;
+use std::hash::{Hash, Hasher};
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 use std::{fmt::Display, fs};
 
-#[derive(Debug, PartialEq, Clone)]
+mod error;
+#[cfg(feature = "c-api")]
+pub mod ffi;
+mod interpolate;
+mod json;
+mod ldlt;
+mod lossy;
+mod lu;
+#[cfg(feature = "ndarray")]
+mod ndarray_interop;
+mod schur;
+mod tridiagonal;
+
+pub use error::MatrixError;
+pub use interpolate::barycentric_interpolate;
+use lu::Lu;
+pub use tridiagonal::TridiagonalMatrix;
+
+const MAX_ITER: usize = 10_000;
+const ITER_TOL: f64 = 1e-10;
+
+/// A dense `rows x cols` matrix of `f64`, stored row-major in `data`.
+///
+/// A `Matrix` owns its entries and has no interior mutability, so it is `Send`
+/// and `Sync`: it can be sent through channels or shared between threads in an
+/// `Arc`.
+#[derive(Debug, Clone)]
 pub struct Matrix {
     pub rows: usize,
     pub cols: usize,
@@ -17,94 +46,155 @@ impl Matrix {
         }
     }
 
-    pub fn from_file(path: &str) -> Self {
-        let content = fs::read_to_string(path).unwrap_or_else(|e| panic!("{e}"));
-        let mut data: Vec<f64> = Vec::new();
-        let mut cols: usize = 0;
-        let mut count: usize = 0;
-
-        for r in content.lines() {
-            let entries: Vec<&str> = r.split_whitespace().collect();
-            let c = entries.len();
-            if count > 0 && cols != c {
-                panic!("Columns don't match");
-            }
-            cols = c;
-            count += 1;
+    /// Builds a matrix from row-major `data`, checking that it holds exactly
+    /// `rows * cols` entries.
+    pub fn from_raw_parts(rows: usize, cols: usize, data: Vec<f64>) -> Result<Self, MatrixError> {
+        if data.len() != rows * cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "A {} by {} matrix needs {} entries, got {}.",
+                rows,
+                cols,
+                rows * cols,
+                data.len()
+            )));
+        }
+        Ok(Self { rows, cols, data })
+    }
 
-            let temp: Vec<f64> = entries
-                .iter()
-                .map(|ent| ent.parse::<f64>().unwrap())
-                .collect();
+    /// Builds a matrix from row-major `data` without checking its length.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee `data.len() == rows * cols`. Every method relies
+    /// on that invariant, and breaking it makes indexing panic or read entries
+    /// from the wrong row.
+    pub unsafe fn from_raw_parts_unchecked(rows: usize, cols: usize, data: Vec<f64>) -> Self {
+        Self { rows, cols, data }
+    }
 
-            for item in temp {
-                data.push(item);
-            }
-        }
+    /// Builds a matrix by copying `rows * cols` row-major entries out of `buf`.
+    pub fn from_buffer(rows: usize, cols: usize, buf: &[f64]) -> Result<Self, MatrixError> {
+        Self::from_raw_parts(rows, cols, buf.to_vec())
+    }
 
+    /// Builds a matrix by copying `len` row-major entries starting at `ptr`, for
+    /// data handed over by C code. Panics if `len != rows * cols`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null, aligned, and valid for reads of `len` `f64`s, and
+    /// that memory must not be written to for the duration of the call. The
+    /// entries are copied, so the caller keeps ownership of the buffer.
+    pub unsafe fn from_raw_ptr(rows: usize, cols: usize, ptr: *const f64, len: usize) -> Self {
+        assert_eq!(
+            len,
+            rows * cols,
+            "A {rows} by {cols} matrix needs {} entries.",
+            rows * cols
+        );
         Self {
-            rows: content.lines().collect::<Vec<_>>().len(),
+            rows,
             cols,
-            data,
+            data: std::slice::from_raw_parts(ptr, len).to_vec(),
+        }
+    }
+
+    /// Consumes the matrix and returns `(rows, cols, data)` without copying.
+    pub fn to_raw_parts(self) -> (usize, usize, Vec<f64>) {
+        (self.rows, self.cols, self.data)
+    }
+
+    /// Reads one row per line. Blank lines and lines starting with `#` are skipped.
+    /// Panics if the file cannot be read or parsed; see `try_from_file`.
+    pub fn from_file(path: &str) -> Self {
+        Self::try_from_file(path).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible `from_file`: returns `Io` if the file cannot be read and
+    /// `ParseError` or `DimensionMismatch` for malformed contents.
+    pub fn try_from_file(path: &str) -> Result<Self, MatrixError> {
+        let content = fs::read_to_string(path)?;
+        let rows = content.lines().filter(|l| {
+            let l = l.trim_start();
+            !l.is_empty() && !l.starts_with('#')
+        });
+        Self::parse_rows(rows)
+    }
+
+    /// Writes the matrix in the format `from_file` reads: one row per line,
+    /// entries separated by spaces, printed with enough digits to round-trip.
+    pub fn write_to_file(&self, path: &str) -> Result<(), MatrixError> {
+        let mut out = String::new();
+        for r in 0..self.rows {
+            out.push_str(&Self::join_row(&self[r]));
+            out.push('\n');
         }
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// The `from_string` format, e.g. `"1 2; 3 4"`, with enough digits to round-trip.
+    pub fn to_string_repr(&self) -> String {
+        (0..self.rows)
+            .map(|r| Self::join_row(&self[r]))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn join_row(row: &[f64]) -> String {
+        row.iter()
+            .map(|x| format!("{x:?}"))
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
+    /// Parses rows separated by `;` or newlines, e.g. `"1 2; 3 4"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics on a malformed entry or ragged rows. Use `input.parse::<Matrix>()`
+    /// to get a `Result` instead.
     pub fn from_string(input: &str) -> Self {
+        input.parse().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn parse_rows<'a>(rows: impl Iterator<Item = &'a str>) -> Result<Self, MatrixError> {
         let mut data: Vec<f64> = Vec::new();
-        let rows: Vec<&str> = input.split(';').collect();
         let mut cols: usize = 0;
         let mut count: usize = 0;
 
-        for r in &rows {
+        for r in rows {
             let entries: Vec<&str> = r.split_whitespace().collect();
             let c = entries.len();
             if count > 0 && cols != c {
-                panic!("Columns don't match");
+                return Err(MatrixError::DimensionMismatch(format!(
+                    "Row {count} has {c} columns, expected {cols}."
+                )));
             }
             cols = c;
             count += 1;
 
-            let temp: Vec<f64> = entries
-                .iter()
-                .map(|ent| ent.parse::<f64>().unwrap())
-                .collect();
-
-            for item in temp {
-                data.push(item);
+            for ent in entries {
+                data.push(ent.parse::<f64>()?);
             }
         }
 
-        Self {
-            rows: rows.len(),
+        Ok(Self {
+            rows: count,
             cols,
             data,
-        }
+        })
     }
 
+    /// Duplicates the matrix. This is the same as `.clone()`, which should be
+    /// preferred: `Matrix` owns its data on the heap, so it is `Clone` but not `Copy`.
+    #[deprecated(note = "use `.clone()`")]
     pub fn copy(&self) -> Self {
-        let mut n_data: Vec<f64> = Vec::new();
-
-        self.data.iter().for_each(|elem| n_data.push(*elem));
-
-        Self {
-            rows: self.rows,
-            cols: self.cols,
-            data: n_data,
-        }
+        self.clone()
     }
 
     pub fn print(&self) {
-        for r in 0..self.rows {
-            print!("[");
-            for c in 0..self.cols {
-                if c == self.cols - 1 {
-                    print!("{:.3}", self[r][c]);
-                } else {
-                    print!("{:.3} ", self[r][c]);
-                }
-            }
-            println!("]");
-        }
+        print!("{self}");
     }
 
     pub fn identity(&mut self) {
@@ -116,13 +206,42 @@ impl Matrix {
         }
     }
 
-    pub fn apply(&mut self, f: impl Fn(f64) -> f64) {
-        self.data = self.data.iter().map(|elem| f(*elem)).collect()
+    /// Returns a new matrix with `f` applied to every entry. Use `apply_inplace`
+    /// to modify `self` instead.
+    #[must_use = "`apply` no longer mutates; use `apply_inplace` to modify the matrix"]
+    pub fn apply(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|elem| f(*elem)).collect(),
+        }
+    }
+
+    /// Applies `f` to every entry in place.
+    pub fn apply_inplace(&mut self, f: impl Fn(f64) -> f64) {
+        self.data.iter_mut().for_each(|elem| *elem = f(*elem));
+    }
+
+    /// Applies `f` to every entry in place, spreading the work over the rayon
+    /// thread pool. Only worth it when `f` is expensive or the matrix is large.
+    #[cfg(feature = "rayon")]
+    pub fn apply_parallel(&mut self, f: impl Fn(f64) -> f64 + Send + Sync) {
+        use rayon::prelude::*;
+        self.data.par_iter_mut().for_each(|elem| *elem = f(*elem));
+    }
+
+    #[deprecated(note = "use `zip_apply`, which borrows `b` and returns a `Result`")]
+    pub fn combine(&self, b: &Matrix, f: impl Fn(f64, f64) -> f64) -> Self {
+        self.zip_apply(b, f).unwrap_or_else(|e| panic!("{e}"))
     }
 
-    pub fn combine(&self, b: Self, f: impl Fn(f64, f64) -> f64) -> Self {
+    /// Combines `self` and `b` element-wise with `f`.
+    pub fn zip_apply(&self, b: &Matrix, f: impl Fn(f64, f64) -> f64) -> Result<Self, MatrixError> {
         if self.rows != b.rows || self.cols != b.cols {
-            panic!("Matrices must be of the same size.");
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Matrices must be of the same size. M1 is {} by {}, M2 is {} by {}.",
+                self.rows, self.cols, b.rows, b.cols
+            )));
         }
         let mut new_matrix = Self::new(self.rows, self.cols);
         new_matrix.data = self
@@ -131,13 +250,13 @@ impl Matrix {
             .zip(b.data.iter())
             .map(|(a, b)| f(*a, *b))
             .collect();
-        new_matrix
+        Ok(new_matrix)
     }
 
-    pub fn dot(&self, b: Self) -> Self {
+    pub fn dot(&self, b: &Matrix) -> Self {
         if self.cols != b.rows {
             panic!(
-                "Dimensions not matched. M1 is {} by {}, M2 is {} by {}.",
+                "Dimensions not matched. M1 is {} by {}, M2 is {} by {}; M1 needs as many columns as M2 has rows.",
                 self.rows, self.cols, b.rows, b.cols
             );
         }
@@ -154,57 +273,77 @@ impl Matrix {
         dp
     }
 
-    pub fn rref(&mut self) {
-        if self[0][0] == 0.0 {
-            self.swap_rows(0);
-        }
-        let mut lead: usize = 0;
-        let rows = self.rows;
-        while lead < rows {
-            for r in 0..rows {
-                let div = self[lead][lead];
-                let mult = self[r][lead] / div;
-
-                if r == lead {
-                    // self[lead] = self[lead].iter().map(|entry| entry / div).collect::<Vec<_>>();
-                    self[lead].iter_mut().for_each(|elem| *elem = (*elem) / div);
-                } else {
-                    for c in 0..self.cols {
-                        self[r][c] -= self[lead][c] * mult;
-                    }
+    /// Computes `alpha * self * b + beta * c` in one pass without allocating
+    /// the product, like BLAS `dgemm`. As in BLAS, `c` is not read when `beta`
+    /// is zero, so NaNs in it do not propagate.
+    pub fn dgemm(
+        &self,
+        b: &Matrix,
+        c: &Matrix,
+        alpha: f64,
+        beta: f64,
+    ) -> Result<Self, MatrixError> {
+        if self.cols != b.rows {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "A is {} by {}, B is {} by {}; A needs as many columns as B has rows.",
+                self.rows, self.cols, b.rows, b.cols
+            )));
+        }
+        if c.rows != self.rows || c.cols != b.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "C is {} by {}, expected {} by {}.",
+                c.rows, c.cols, self.rows, b.cols
+            )));
+        }
+        let mut out = if beta == 0.0 {
+            Self::new(c.rows, c.cols)
+        } else {
+            c.apply(|x| beta * x)
+        };
+        for i in 0..self.rows {
+            let row = &mut out.data[i * b.cols..(i + 1) * b.cols];
+            for (k, a_ik) in self[i].iter().enumerate() {
+                let scale = alpha * a_ik;
+                for (o, b_kj) in row.iter_mut().zip(&b[k]) {
+                    *o += scale * b_kj;
                 }
             }
-            lead += 1;
         }
-        self.correct();
+        Ok(out)
     }
 
-    pub fn cofactor(&self, expanded_row: usize, j: usize) -> f64 {
-        let mut cut: Vec<Vec<f64>> = Vec::new();
-        for r in 0..self.rows {
-            if r == expanded_row {
-                continue;
-            }
-            let mut v: Vec<f64> = Vec::new();
-            for c in 0..self.cols {
-                if c == j {
-                    continue;
-                }
-                v.push(self[r][c]);
-            }
-            cut.push(v);
-        }
-        let flattened = cut.clone().into_iter().flatten().collect();
-        let n_r = cut.len();
-        let n_c = cut[0].len();
-        let minor = Self {
-            rows: n_r,
-            cols: n_c,
-            data: flattened,
+    pub fn rref(&mut self) {
+        *self = self.rref_with_pivot_info().0;
+    }
+
+    /// The matrix left after deleting row `i` and column `j`.
+    pub fn minor(&self, i: usize, j: usize) -> Matrix {
+        assert!(
+            i < self.rows && j < self.cols,
+            "Minor ({i}, {j}) is out of bounds for a {} by {} matrix.",
+            self.rows,
+            self.cols
+        );
+        let data = (0..self.rows)
+            .filter(|&r| r != i)
+            .flat_map(|r| {
+                self[r]
+                    .iter()
+                    .enumerate()
+                    .filter(|&(c, _)| c != j)
+                    .map(|(_, &x)| x)
+            })
+            .collect();
+        Self {
+            rows: self.rows - 1,
+            cols: self.cols - 1,
+            data,
         }
-        .det();
-        let base: i32 = -1;
-        minor * f64::from(base.pow((expanded_row + j) as u32))
+    }
+
+    pub fn cofactor(&self, i: usize, j: usize) -> f64 {
+        let sign = if (i + j).is_multiple_of(2) { 1.0 } else { -1.0 };
+        sign * self.minor(i, j).det()
     }
 
     pub fn det(&self) -> f64 {
@@ -214,17 +353,8 @@ impl Matrix {
                 self
             );
         }
-        if self.rows == 2 && self.cols == 2 {
-            self[0][0] * self[1][1] - self[0][1] * self[1][0]
-        } else {
-            let row: usize = 1;
-            let mut det = 0.0;
-
-            for j in 0..self[row].len() {
-                det += self.cofactor(row, j) * self[row][j];
-            }
-            det
-        }
+        // The factorization only fails on an exactly zero pivot column.
+        Lu::new(self).map_or(0.0, |lu| lu.det())
     }
 
     pub fn transpose(&self) -> Self {
@@ -237,129 +367,3858 @@ impl Matrix {
         t
     }
 
+    /// Sum of the main diagonal entries. For an `m x n` matrix this is the sum
+    /// of the first `min(m, n)` diagonal entries, which keeps `tr(A^T B)` equal
+    /// to the Frobenius inner product for rectangular `A` and `B`.
     pub fn trace(&self) -> f64 {
-        if self.rows != self.cols {
-            panic!(
-                "Trace requires matrix to be square. Input matrix was {:?}.",
-                self
-            );
+        self.diag_sum()
+    }
+
+    pub fn inverse(&self) -> Self {
+        let lu = match Lu::new(self) {
+            Ok(lu) => lu,
+            Err(MatrixError::Singular) => panic!("Determinant is 0. No inverse."),
+            Err(e) => panic!("{e}"),
+        };
+        lu.solve(&Self::eye(self.rows))
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// The transposed matrix of cofactors, so that `A * adj(A) == det(A) * I`.
+    pub fn adjugate(&self) -> Result<Self, MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut adj = Self::new(n, n);
+        for row in 0..n {
+            for col in 0..n {
+                adj[col][row] = self.cofactor(row, col);
+            }
         }
-        let mut t: f64 = 0.0;
-        for i in 0..self.rows {
-            t += self[i][i];
+        Ok(adj)
+    }
+
+    /// Returns a copy of `self` with the main diagonal replaced by `d`.
+    pub fn with_diagonal(&self, d: &[f64]) -> Result<Self, MatrixError> {
+        let n = self.rows.min(self.cols);
+        if d.len() != n {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Diagonal has {} entries, expected {}.",
+                d.len(),
+                n
+            )));
         }
-        t
+        let mut m = self.clone();
+        for (i, elem) in d.iter().enumerate() {
+            m[i][i] = *elem;
+        }
+        Ok(m)
     }
 
-    pub fn inverse(&self) -> Self {
-        let d = self.det();
-        if d == 0.0 {
-            panic!("Determinant is 0. No inverse.");
+    /// Largest eigenvalue magnitude, read off the diagonal blocks of the real
+    /// Schur form: `|t|` for a 1x1 block and `sqrt(det)` for a 2x2 block holding
+    /// a complex pair. Unlike power iteration this does not depend on a start
+    /// vector, and handles Jordan blocks and complex dominant pairs. Returns
+    /// `NoConvergence` only if the Schur iteration fails, and 0.0 for a 0x0
+    /// matrix.
+    pub fn spectral_radius(&self) -> Result<f64, MatrixError> {
+        let (_, t) = self.schur()?;
+        Ok(t.schur_blocks()
+            .iter()
+            .map(|b| {
+                let i = b.start;
+                if b.len() == 1 {
+                    t[i][i].abs()
+                } else {
+                    (t[i][i] * t[i + 1][i + 1] - t[i][i + 1] * t[i + 1][i]).sqrt()
+                }
+            })
+            .fold(0.0, f64::max))
+    }
+
+    /// Whether the spectral radius is strictly below 1, i.e. `A^k -> 0`.
+    pub fn is_convergent(&self) -> Result<bool, MatrixError> {
+        Ok(self.spectral_radius()? < 1.0)
+    }
+
+    /// Returns a copy of `self` with `f` applied to every element of row `r`.
+    pub fn apply_row(&self, r: usize, f: impl Fn(f64) -> f64) -> Result<Self, MatrixError> {
+        let mut m = self.clone();
+        m.apply_row_inplace(r, f)?;
+        Ok(m)
+    }
+
+    /// Returns a copy of `self` with `f` applied to every element of column `c`.
+    pub fn apply_col(&self, c: usize, f: impl Fn(f64) -> f64) -> Result<Self, MatrixError> {
+        let mut m = self.clone();
+        m.apply_col_inplace(c, f)?;
+        Ok(m)
+    }
+
+    pub fn apply_row_inplace(
+        &mut self,
+        r: usize,
+        f: impl Fn(f64) -> f64,
+    ) -> Result<(), MatrixError> {
+        if r >= self.rows {
+            return Err(MatrixError::IndexOutOfBounds {
+                index: r,
+                len: self.rows,
+            });
+        }
+        self[r].iter_mut().for_each(|elem| *elem = f(*elem));
+        Ok(())
+    }
+
+    pub fn apply_col_inplace(
+        &mut self,
+        c: usize,
+        f: impl Fn(f64) -> f64,
+    ) -> Result<(), MatrixError> {
+        if c >= self.cols {
+            return Err(MatrixError::IndexOutOfBounds {
+                index: c,
+                len: self.cols,
+            });
+        }
+        for r in 0..self.rows {
+            self[r][c] = f(self[r][c]);
         }
+        Ok(())
+    }
 
-        let mut inv = Self::new(self.rows, self.cols);
+    /// Builds a new matrix from `f` applied to each row. `f` must return a row of
+    /// the same length.
+    pub fn map_rows(&self, f: impl Fn(&[f64]) -> Vec<f64>) -> Result<Self, MatrixError> {
+        let mut m = Self::new(self.rows, self.cols);
+        for r in 0..self.rows {
+            let row = f(&self[r]);
+            if row.len() != self.cols {
+                return Err(MatrixError::DimensionMismatch(format!(
+                    "Row {} was mapped to {} entries, expected {}.",
+                    r,
+                    row.len(),
+                    self.cols
+                )));
+            }
+            m[r].copy_from_slice(&row);
+        }
+        Ok(m)
+    }
 
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                inv[row][col] = self.cofactor(row, col);
+    /// Builds a new matrix from `f` applied to each column. `f` must return a
+    /// column of the same length.
+    pub fn map_cols(&self, f: impl Fn(&[f64]) -> Vec<f64>) -> Result<Self, MatrixError> {
+        let t = self.transpose();
+        let mut m = Self::new(self.rows, self.cols);
+        for c in 0..self.cols {
+            let col = f(&t[c]);
+            if col.len() != self.rows {
+                return Err(MatrixError::DimensionMismatch(format!(
+                    "Column {} was mapped to {} entries, expected {}.",
+                    c,
+                    col.len(),
+                    self.rows
+                )));
+            }
+            for (r, elem) in col.into_iter().enumerate() {
+                m[r][c] = elem;
             }
         }
+        Ok(m)
+    }
 
-        inv.correct();
-        inv = inv.transpose();
-        inv.apply(|x| x / d);
-        inv
+    /// Folds each row to a single value, starting from `init`.
+    pub fn fold_rows(&self, init: f64, f: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+        (0..self.rows)
+            .map(|r| self[r].iter().fold(init, |acc, elem| f(acc, *elem)))
+            .collect()
     }
 
-    fn swap_rows(&mut self, row: usize) {
-        let mut n_r = 0;
+    /// Folds each column to a single value, starting from `init`.
+    pub fn fold_cols(&self, init: f64, f: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+        (0..self.cols)
+            .map(|c| (0..self.rows).fold(init, |acc, r| f(acc, self[r][c])))
+            .collect()
+    }
+
+    /// Row and column of the first element, in row-major order, matching `f`.
+    pub fn position(&self, f: impl Fn(f64) -> bool) -> Option<(usize, usize)> {
+        self.data
+            .iter()
+            .position(|elem| f(*elem))
+            .map(|i| (i / self.cols, i % self.cols))
+    }
+
+    /// First element, in row-major order, matching `f`.
+    pub fn find(&self, f: impl Fn(f64) -> bool) -> Option<f64> {
+        self.data.iter().copied().find(|elem| f(*elem))
+    }
+
+    /// Prints `self` as a table with row and column headers. Missing labels are
+    /// left blank and extra labels are ignored.
+    pub fn print_labeled(&self, row_labels: &[&str], col_labels: &[&str]) {
+        print!("{}", self.labeled_table(row_labels, col_labels));
+    }
+
+    fn labeled_table(&self, row_labels: &[&str], col_labels: &[&str]) -> String {
+        fn label<'a>(labels: &[&'a str], i: usize) -> &'a str {
+            labels.get(i).copied().unwrap_or("")
+        }
+        let label_width = (0..self.rows)
+            .map(|r| label(row_labels, r).chars().count())
+            .max()
+            .unwrap_or(0);
+        let widths: Vec<usize> = (0..self.cols)
+            .map(|c| {
+                (0..self.rows)
+                    .map(|r| format!("{:.3}", self[r][c]).len())
+                    .chain(std::iter::once(label(col_labels, c).chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut header = format!("{:label_width$}", "");
+        for (c, width) in widths.iter().enumerate() {
+            header += &format!(" {:>width$}", label(col_labels, c));
+        }
+        let mut table = format!("{}\n", header.trim_end());
         for r in 0..self.rows {
-            if self[r][0] > 0.0 {
-                n_r = r;
+            table += &format!("{:label_width$}", label(row_labels, r));
+            for (c, width) in widths.iter().enumerate() {
+                table += &format!(" {:>width$.3}", self[r][c]);
+            }
+            table += "\n";
+        }
+        table
+    }
+
+    /// Householder reflection `H = I - 2 v v^T / (v^T v)` across the hyperplane
+    /// orthogonal to `v`. `v` need not be normalized; a zero `v` gives `I`.
+    pub fn householder(v: &[f64]) -> Self {
+        let n = v.len();
+        let mut h = Self::eye(n);
+        let norm_sq: f64 = v.iter().map(|x| x * x).sum();
+        if norm_sq == 0.0 {
+            return h;
+        }
+        for i in 0..n {
+            for j in 0..n {
+                h[i][j] -= 2.0 * v[i] * v[j] / norm_sq;
+            }
+        }
+        h
+    }
+
+    /// Counter-clockwise rotation of the plane by `angle_rad`.
+    pub fn rotation_2d(angle_rad: f64) -> Self {
+        let (s, c) = angle_rad.sin_cos();
+        Self {
+            rows: 2,
+            cols: 2,
+            data: vec![c, -s, s, c],
+        }
+    }
+
+    /// Rotation by `angle_rad` about the x axis.
+    pub fn rotation_3d_x(angle_rad: f64) -> Self {
+        let (s, c) = angle_rad.sin_cos();
+        Self {
+            rows: 3,
+            cols: 3,
+            data: vec![1.0, 0.0, 0.0, 0.0, c, -s, 0.0, s, c],
+        }
+    }
+
+    /// Rotation by `angle_rad` about the y axis.
+    pub fn rotation_3d_y(angle_rad: f64) -> Self {
+        let (s, c) = angle_rad.sin_cos();
+        Self {
+            rows: 3,
+            cols: 3,
+            data: vec![c, 0.0, s, 0.0, 1.0, 0.0, -s, 0.0, c],
+        }
+    }
+
+    /// Rotation by `angle_rad` about the z axis.
+    pub fn rotation_3d_z(angle_rad: f64) -> Self {
+        let (s, c) = angle_rad.sin_cos();
+        Self {
+            rows: 3,
+            cols: 3,
+            data: vec![c, -s, 0.0, s, c, 0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// Rotation by `angle` about `axis` using Rodrigues' formula
+    /// `R = I + sin(a) K + (1 - cos(a)) K^2`. The axis is normalized first; a zero
+    /// axis gives `I`.
+    pub fn rotation_3d_axis(axis: &[f64; 3], angle: f64) -> Self {
+        let norm = axis.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return Self::eye(3);
+        }
+        let [x, y, z] = axis.map(|a| a / norm);
+        let k = Self {
+            rows: 3,
+            cols: 3,
+            data: vec![0.0, -z, y, z, 0.0, -x, -y, x, 0.0],
+        };
+        let (s, c) = angle.sin_cos();
+        let k_sq = k.dot(&k);
+
+        let mut r = Self::eye(3);
+        for i in 0..9 {
+            r.data[i] += s * k.data[i] + (1.0 - c) * k_sq.data[i];
+        }
+        r
+    }
+
+    /// Orthogonal projection `P = v v^T / (v^T v)` onto the line spanned by `v`.
+    /// A zero `v` spans nothing, so the result is the zero matrix.
+    pub fn projection_onto(v: &[f64]) -> Self {
+        let n = v.len();
+        let mut p = Self::new(n, n);
+        let norm_sq: f64 = v.iter().map(|x| x * x).sum();
+        if norm_sq == 0.0 {
+            return p;
+        }
+        for i in 0..n {
+            for j in 0..n {
+                p[i][j] = v[i] * v[j] / norm_sq;
+            }
+        }
+        p
+    }
+
+    /// Hat matrix `A (A^T A)^-1 A^T` projecting onto the column space of `self`.
+    /// Panics if the columns are linearly dependent.
+    pub fn projection_onto_columns(&self) -> Self {
+        let t = self.transpose();
+        let gram_inv = t.dot(self).inverse();
+        self.dot(&gram_inv).dot(&t)
+    }
+
+    /// Centering matrix `C = I - (1/n) 1 1^T`. `C x` subtracts the mean of `x`
+    /// from each entry.
+    pub fn centering(n: usize) -> Self {
+        let off = -1.0 / n as f64;
+        let mut c = Self {
+            rows: n,
+            cols: n,
+            data: vec![off; n * n],
+        };
+        for i in 0..n {
+            c[i][i] = 1.0 + off;
+        }
+        c
+    }
+
+    /// Finite difference operator of the given order on vectors of length `n`:
+    /// `D x` has entries `x[i + 1] - x[i]` for `order == 1`, and higher orders are
+    /// the products `D_{n - order + 1} ... D_n`, giving `n - order` rows. Orders
+    /// of `n` or more give a matrix with no rows.
+    pub fn difference_matrix(n: usize, order: usize) -> Self {
+        let mut d = Self::eye(n);
+        for k in 0..order.min(n) {
+            let m = n - k;
+            let mut step = Self::new(m - 1, m);
+            for i in 0..m - 1 {
+                step[i][i] = -1.0;
+                step[i][i + 1] = 1.0;
+            }
+            d = step.dot(&d);
+        }
+        d
+    }
+
+    /// Drazin inverse `A^D`, computed with Cline's method: repeated full-rank
+    /// factorizations `C_i B_i = B_{i+1} C_{i+1}` until `C_k B_k` is invertible,
+    /// then `A^D = B_1..B_k (C_k B_k)^-(k+1) C_k..C_1`. For invertible matrices it
+    /// equals the ordinary inverse, and it is zero for nilpotent ones. Returns
+    /// `Singular` if `C_k B_k` passes the rank test but its LU still hits a zero
+    /// pivot.
+    pub fn drazin_inverse(&self) -> Result<Self, MatrixError> {
+        self.check_square()?;
+        let mut bs: Vec<Self> = Vec::new();
+        let mut cs: Vec<Self> = Vec::new();
+        let mut m = self.clone();
+
+        loop {
+            let (b, c) = m.rank_factorization();
+            if b.cols == 0 {
+                return Ok(Self::new(self.rows, self.cols));
+            }
+            let r = b.cols;
+            m = c.dot(&b);
+            bs.push(b);
+            cs.push(c);
+            if m.rank() == r {
                 break;
             }
         }
-        let temp: Vec<f64> = self[row].to_vec();
-        for c in 0..self.cols {
-            self[row][c] = self[n_r][c];
-            self[n_r][c] = temp[n_r * self.cols + c];
+
+        let k = bs.len();
+        let cb_inv = Lu::new(&m)?.solve(&Self::eye(m.rows))?;
+        let mut middle = Self::eye(cb_inv.rows);
+        for _ in 0..=k {
+            middle = middle.dot(&cb_inv);
         }
+        let mut result = middle;
+        for b in bs.iter().rev() {
+            result = b.dot(&result);
+        }
+        for c in cs.iter().rev() {
+            result = result.dot(c);
+        }
+        Ok(result)
     }
 
-    fn correct(&mut self) {
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                let elem = self[row][col];
-                if elem == -0.0 {
-                    self[row][col] = 0.0;
+    /// ILU(0) incomplete LU factorization: unit lower `L` and upper `U` that keep
+    /// the sparsity pattern of `self`, dropping any fill-in. `L U` matches `self`
+    /// on its nonzero entries only, so how well it works as a preconditioner
+    /// depends on how much fill-in an exact LU would have produced. It is exact
+    /// for tridiagonal and other matrices without fill-in.
+    pub fn ilu0(&self) -> Result<(Self, Self), MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut a = self.clone();
+
+        for i in 1..n {
+            for k in 0..i {
+                if self[i][k] == 0.0 {
+                    continue;
                 }
-                let floored = elem.floor();
-                if elem - floored > 0.9999999 {
-                    self[row][col] = elem.round();
+                if a[k][k] == 0.0 {
+                    return Err(MatrixError::Singular);
                 }
-                if elem > 0.0 && elem < 0.000001 {
-                    self[row][col] = 0.0;
+                a[i][k] /= a[k][k];
+                for j in k + 1..n {
+                    if self[i][j] != 0.0 {
+                        a[i][j] -= a[i][k] * a[k][j];
+                    }
                 }
-                if elem < 0.0 && elem > -0.00001 {
-                    self[row][col] = 0.0;
+            }
+        }
+
+        let mut l = Self::eye(n);
+        let mut u = Self::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                if j < i {
+                    l[i][j] = a[i][j];
+                } else {
+                    u[i][j] = a[i][j];
                 }
             }
         }
+        Ok((l, u))
     }
-}
 
-impl Display for Matrix {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for r in 0..self.rows {
-            write!(f, "[")?;
-            for c in 0..self.cols {
-                if c == self.cols - 1 {
-                    write!(f, "{:.3}", self[r][c])?;
-                } else {
-                    write!(f, "{:.3} ", self[r][c])?;
+    /// Thin singular value decomposition `A = U diag(s) V^T` by one-sided Jacobi
+    /// rotations. With `k = min(rows, cols)`, `U` is `rows x k`, `V` is `cols x k`,
+    /// both with orthonormal columns, and `s` holds the `k` singular values in
+    /// decreasing order.
+    pub fn svd(&self) -> Result<(Self, Vec<f64>, Self), MatrixError> {
+        if self.rows < self.cols {
+            let (v, s, u) = self.transpose().svd()?;
+            return Ok((u, s, v));
+        }
+        let n = self.cols;
+        let mut u = self.clone();
+        let mut v = Self::eye(n);
+
+        let mut converged = false;
+        for _ in 0..MAX_ITER {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let (mut alpha, mut beta, mut gamma) = (0.0, 0.0, 0.0);
+                    for i in 0..u.rows {
+                        alpha += u[i][p] * u[i][p];
+                        beta += u[i][q] * u[i][q];
+                        gamma += u[i][p] * u[i][q];
+                    }
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    u.rotate_cols(p, q, c, s);
+                    v.rotate_cols(p, q, c, s);
                 }
             }
-            writeln!(f, "]")?;
+            if !rotated {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(MatrixError::NoConvergence {
+                iterations: MAX_ITER,
+            });
         }
 
-        Ok(())
+        let norms: Vec<f64> = (0..n)
+            .map(|j| (0..u.rows).map(|i| u[i][j] * u[i][j]).sum::<f64>().sqrt())
+            .collect();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|a, b| norms[*b].total_cmp(&norms[*a]));
+
+        let tol = f64::EPSILON * self.rows as f64 * norms.iter().fold(0.0_f64, |a, b| a.max(*b));
+        let mut u_sorted = Self::new(self.rows, n);
+        let mut v_sorted = Self::new(n, n);
+        let mut s = Vec::with_capacity(n);
+        for (j, &k) in order.iter().enumerate() {
+            let sigma = if norms[k] > tol { norms[k] } else { 0.0 };
+            for i in 0..self.rows {
+                u_sorted[i][j] = if sigma > 0.0 { u[i][k] / sigma } else { 0.0 };
+            }
+            for i in 0..n {
+                v_sorted[i][j] = v[i][k];
+            }
+            s.push(sigma);
+        }
+        u_sorted.complete_orthonormal_cols();
+        Ok((u_sorted, s, v_sorted))
     }
-}
 
-impl Index<usize> for Matrix {
-    type Output = [f64];
+    /// Rank-`k` randomized SVD (Halko, Martinsson and Tropp). `self` is sketched
+    /// with a Gaussian test matrix of `k + n_oversampling` columns, the sketch is
+    /// orthonormalized into `Q`, and the small matrix `Q^T A` is decomposed
+    /// exactly. Returns `(U, s, V)` with `A ~ U diag(s) V^T`, `U` being
+    /// `rows x k` and `V` being `cols x k`.
+    pub fn randomized_svd(
+        &self,
+        k: usize,
+        n_oversampling: usize,
+        rng: &mut impl Rng,
+    ) -> Result<(Self, Vec<f64>, Self), MatrixError> {
+        let max_rank = self.rows.min(self.cols);
+        if k == 0 || k > max_rank {
+            return Err(MatrixError::InvalidParameter(format!(
+                "Rank k must be between 1 and {max_rank}, got {k}."
+            )));
+        }
+        let l = (k + n_oversampling).min(max_rank);
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index * self.cols..(index + 1) * self.cols]
+        let mut omega = Self::new(self.cols, l);
+        omega.data = (0..self.cols * l).map(|_| standard_normal(rng)).collect();
+        let mut q = self.dot(&omega);
+        q.orthonormalize_cols();
+
+        let b = q.transpose().dot(self);
+        let (ub, s, v) = b.svd()?;
+        let u = q.dot(&ub);
+
+        Ok((u.first_cols(k), s[..k].to_vec(), v.first_cols(k)))
     }
-}
 
-impl IndexMut<usize> for Matrix {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.data[index * self.cols..(index + 1) * self.cols]
+    /// Keeps the top `k` singular triplets of `svd`, so `U_k diag(s_k) V_k^T` is
+    /// the best rank-`k` approximation of `self` (Eckart-Young). Its error is
+    /// `s[k]` in the spectral norm and `sqrt(s[k]^2 + s[k+1]^2 + ...)` in the
+    /// Frobenius norm, where `s` are the full singular values.
+    pub fn truncated_svd(&self, k: usize) -> Result<(Self, Vec<f64>, Self), MatrixError> {
+        let max_rank = self.rows.min(self.cols);
+        if k > max_rank {
+            return Err(MatrixError::InvalidParameter(format!(
+                "Rank k must be at most {max_rank}, got {k}."
+            )));
+        }
+        let (u, s, v) = self.svd()?;
+        Ok((u.first_cols(k), s[..k].to_vec(), v.first_cols(k)))
     }
-}
+
+    /// Arnoldi iteration with modified Gram-Schmidt. Builds `Q` (`n x k`) with
+    /// orthonormal columns spanning the Krylov subspace `{b, Ab, ..., A^(k-1) b}`
+    /// and the upper Hessenberg `H = Q^T A Q` (`k x k`), so `A Q ~ Q H`.
+    ///
+    /// If the subspace becomes invariant before `k` steps (breakdown), the
+    /// iteration stops early and `Q` and `H` have as many columns as steps were
+    /// completed; in that case `A Q = Q H` holds exactly.
+    pub fn arnoldi(&self, b: &[f64], k: usize) -> Result<(Self, Self), MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        if b.len() != n {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Start vector has {} entries, expected {}.",
+                b.len(),
+                n
+            )));
+        }
+        if k == 0 || k > n {
+            return Err(MatrixError::InvalidParameter(format!(
+                "Number of steps k must be between 1 and {n}, got {k}."
+            )));
+        }
+        let b_norm = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if b_norm == 0.0 {
+            return Err(MatrixError::InvalidParameter(
+                "Start vector must be nonzero.".to_string(),
+            ));
+        }
+
+        let mut basis: Vec<Vec<f64>> = vec![b.iter().map(|x| x / b_norm).collect()];
+        let mut h = Self::new(k, k);
+        let mut steps = k;
+        for j in 0..k {
+            let mut w: Vec<f64> = (0..n)
+                .map(|i| {
+                    self[i]
+                        .iter()
+                        .zip(basis[j].iter())
+                        .map(|(a, q)| a * q)
+                        .sum()
+                })
+                .collect();
+            let w_norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
+            for (i, q) in basis.iter().enumerate() {
+                h[i][j] = q.iter().zip(w.iter()).map(|(a, b)| a * b).sum();
+                w.iter_mut()
+                    .zip(q.iter())
+                    .for_each(|(x, q)| *x -= h[i][j] * q);
+            }
+            if j + 1 == k {
+                break;
+            }
+            let next_norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if next_norm <= ITER_TOL * w_norm.max(f64::MIN_POSITIVE) {
+                steps = j + 1;
+                break;
+            }
+            h[j + 1][j] = next_norm;
+            basis.push(w.iter().map(|x| x / next_norm).collect());
+        }
+
+        let mut q = Self::new(n, steps);
+        for (j, col) in basis.iter().take(steps).enumerate() {
+            for i in 0..n {
+                q[i][j] = col[i];
+            }
+        }
+        let mut h_steps = Self::new(steps, steps);
+        for i in 0..steps {
+            h_steps[i].copy_from_slice(&h[i][..steps]);
+        }
+        Ok((q, h_steps))
+    }
+
+    /// Householder reduction to upper Hessenberg form. Returns `(Q, H)` with `Q`
+    /// orthogonal, `H` zero below its first subdiagonal, and `A = Q H Q^T`.
+    pub fn to_hessenberg(&self) -> Result<(Self, Self), MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut h = self.clone();
+        let mut q = Self::eye(n);
+
+        for k in 0..n.saturating_sub(2) {
+            let x: Vec<f64> = (k + 1..n).map(|i| h[i][k]).collect();
+            let Some(v) = householder_vector(&x) else {
+                continue;
+            };
+            h.reflect_rows(&v, k + 1);
+            h.reflect_cols(&v, k + 1);
+            q.reflect_cols(&v, k + 1);
+            for i in k + 2..n {
+                h[i][k] = 0.0;
+            }
+        }
+        Ok((q, h))
+    }
+
+    /// Polar decomposition `A = U P` from the SVD `A = W diag(s) V^T`:
+    /// `U = W V^T` has orthonormal columns (orthogonal when `self` is square) and
+    /// `P = V diag(s) V^T` is symmetric positive semidefinite. Requires
+    /// `rows >= cols`.
+    pub fn polar(&self) -> Result<(Self, Self), MatrixError> {
+        if self.rows < self.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Polar decomposition needs at least as many rows as columns. Input matrix was {} by {}.",
+                self.rows, self.cols
+            )));
+        }
+        let (w, s, v) = self.svd()?;
+        let vt = v.transpose();
+        let u = w.dot(&vt);
+        let mut vs = v;
+        for i in 0..vs.rows {
+            for (j, sigma) in s.iter().enumerate() {
+                vs[i][j] *= sigma;
+            }
+        }
+        Ok((u, vs.dot(&vt)))
+    }
+
+    /// Ridge (Tikhonov) regression: minimizes `||A x - b||^2 + lambda ||x||^2` by
+    /// solving `(A^T A + lambda I) x = A^T b`. Any `lambda > 0` keeps the system
+    /// nonsingular, even for rank-deficient `A`. `b` may have several columns.
+    pub fn ridge_regression(&self, b: &Matrix, lambda: f64) -> Result<Self, MatrixError> {
+        if lambda < 0.0 || lambda.is_nan() {
+            return Err(MatrixError::InvalidParameter(format!(
+                "lambda must be nonnegative, got {lambda}."
+            )));
+        }
+        if b.rows != self.rows {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "A is {} by {}, b is {} by {}.",
+                self.rows, self.cols, b.rows, b.cols
+            )));
+        }
+        let t = self.transpose();
+        let mut gram = t.dot(self);
+        for i in 0..gram.rows {
+            gram[i][i] += lambda;
+        }
+        Lu::new(&gram)?.solve(&t.dot(b))
+    }
+
+    /// Whether every entry more than `lower` below or `upper` above the main
+    /// diagonal is within `tol` of zero.
+    pub fn is_banded(&self, lower: usize, upper: usize, tol: f64) -> bool {
+        let (l, u) = self.bandwidth(tol);
+        l <= lower && u <= upper
+    }
+
+    /// Lower and upper bandwidth: the largest distance below and above the
+    /// main diagonal of any entry larger than `tol` in magnitude.
+    pub fn bandwidth(&self, tol: f64) -> (usize, usize) {
+        let mut lower = 0;
+        let mut upper = 0;
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                if self[r][c].abs() > tol {
+                    if r > c {
+                        lower = lower.max(r - c);
+                    } else {
+                        upper = upper.max(c - r);
+                    }
+                }
+            }
+        }
+        (lower, upper)
+    }
+
+    /// Lanczos iteration for symmetric matrices with full modified Gram-Schmidt
+    /// reorthogonalization. Runs up to `k` steps from a fixed start vector and
+    /// returns the Ritz values (eigenvalues of the tridiagonal `T = Q^T A Q`, in
+    /// ascending order) together with the orthonormal basis `Q`. The extreme Ritz
+    /// values approximate the extreme eigenvalues of `self`.
+    ///
+    /// The iteration stops early once the next off-diagonal of `T` falls below
+    /// `tol`, as the basis then spans an invariant subspace; `Q` has one column
+    /// per completed step.
+    pub fn lanczos(&self, k: usize, tol: f64) -> Result<(Vec<f64>, Self), MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        if k == 0 || k > n {
+            return Err(MatrixError::InvalidParameter(format!(
+                "Number of steps k must be between 1 and {n}, got {k}."
+            )));
+        }
+        if !self.is_symmetric(tol) {
+            return Err(MatrixError::InvalidParameter(
+                "Lanczos requires a symmetric matrix.".to_string(),
+            ));
+        }
+
+        let start: Vec<f64> = (0..n).map(|i| 1.0 / (i + 1) as f64).collect();
+        let start_norm = start.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let mut basis: Vec<Vec<f64>> = vec![start.iter().map(|x| x / start_norm).collect()];
+        let mut alpha: Vec<f64> = Vec::new();
+        let mut beta: Vec<f64> = Vec::new();
+
+        for j in 0..k {
+            let q = &basis[j];
+            let mut w: Vec<f64> = (0..n)
+                .map(|i| self[i].iter().zip(q.iter()).map(|(a, b)| a * b).sum())
+                .collect();
+            alpha.push(q.iter().zip(w.iter()).map(|(a, b)| a * b).sum());
+            for _ in 0..2 {
+                for prev in &basis {
+                    let proj: f64 = prev.iter().zip(w.iter()).map(|(a, b)| a * b).sum();
+                    w.iter_mut()
+                        .zip(prev.iter())
+                        .for_each(|(x, p)| *x -= proj * p);
+                }
+            }
+            if j + 1 == k {
+                break;
+            }
+            let norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm <= tol {
+                break;
+            }
+            beta.push(norm);
+            basis.push(w.iter().map(|x| x / norm).collect());
+        }
+
+        let steps = alpha.len();
+        let mut t = Self::new(steps, steps);
+        for i in 0..steps {
+            t[i][i] = alpha[i];
+            if i + 1 < steps {
+                t[i][i + 1] = beta[i];
+                t[i + 1][i] = beta[i];
+            }
+        }
+        let mut q = Self::new(n, steps);
+        for (j, col) in basis.iter().take(steps).enumerate() {
+            for i in 0..n {
+                q[i][j] = col[i];
+            }
+        }
+        Ok((t.symmetric_eigen()?.0, q))
+    }
+
+    /// Estimates the 1-norm condition number `||A||_1 ||A^-1||_1` with Hager's
+    /// algorithm (as refined by Higham in LAPACK's `dlacon`). `||A^-1||_1` is
+    /// estimated from a few solves with the LU factors instead of forming the
+    /// inverse, so the cost is one O(n^3) factorization plus O(n^2) per step.
+    /// The estimate never exceeds the true value and is usually within a small
+    /// factor of it. Returns `Singular` for an exact zero pivot, and infinity
+    /// when solving with the factors overflows.
+    pub fn condition_estimate_1norm(&self) -> Result<f64, MatrixError> {
+        let lu = Lu::new(self)?;
+        let n = self.rows;
+        if n == 0 {
+            return Ok(0.0);
+        }
+        let solve = |x: &[f64]| -> Result<Vec<f64>, MatrixError> {
+            let b = Self {
+                rows: n,
+                cols: 1,
+                data: x.to_vec(),
+            };
+            Ok(lu.solve(&b)?.data)
+        };
+        let norm_1 = |x: &[f64]| x.iter().map(|v| v.abs()).sum::<f64>();
+
+        let mut x = vec![1.0 / n as f64; n];
+        let mut estimate = 0.0;
+        for _ in 0..5 {
+            let y = solve(&x)?;
+            estimate = norm_1(&y);
+            if !estimate.is_finite() {
+                return Ok(f64::INFINITY);
+            }
+            let xi: Vec<f64> = y
+                .iter()
+                .map(|v| if *v >= 0.0 { 1.0 } else { -1.0 })
+                .collect();
+            let z = lu.solve_transpose(&xi);
+            let (j, z_max) = z.iter().map(|v| v.abs()).enumerate().fold(
+                (0, f64::NEG_INFINITY),
+                |acc, (i, v)| if v > acc.1 { (i, v) } else { acc },
+            );
+            let ztx: f64 = z.iter().zip(x.iter()).map(|(a, b)| a * b).sum();
+            if z_max <= ztx {
+                break;
+            }
+            x = vec![0.0; n];
+            x[j] = 1.0;
+        }
+
+        // Higham's safeguard against the cases where the iteration stalls early.
+        let alt: Vec<f64> = (0..n)
+            .map(|i| {
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                sign * (1.0 + i as f64 / (n as f64 - 1.0).max(1.0))
+            })
+            .collect();
+        estimate = estimate.max(2.0 * norm_1(&solve(&alt)?) / (3.0 * n as f64));
+
+        let a_norm = (0..n)
+            .map(|c| (0..n).map(|r| self[r][c].abs()).sum::<f64>())
+            .fold(0.0, f64::max);
+        Ok(a_norm * estimate)
+    }
+
+    /// Solves `A X = B` through an LU factorization with partial pivoting.
+    pub fn solve(&self, b: &Matrix) -> Result<Self, MatrixError> {
+        Lu::new(self)?.solve(b)
+    }
+
+    /// Solves `A X = B` with iterative refinement: after the LU solve, the residual
+    /// `R = B - A X` is computed with compensated (twice-working-precision) dot
+    /// products, `A D = R` is solved with the same factors and `X += D`. Stops
+    /// once the correction is negligible relative to `X`, and returns
+    /// `NoConvergence` if that does not happen within `max_iter` refinements.
+    /// With `max_iter == 0` this is the plain LU solve.
+    pub fn solve_with_refinement(&self, b: &Matrix, max_iter: usize) -> Result<Self, MatrixError> {
+        let lu = Lu::new(self)?;
+        let mut x = lu.solve(b)?;
+        if max_iter == 0 {
+            return Ok(x);
+        }
+
+        for _ in 0..max_iter {
+            let mut r = Self::new(b.rows, b.cols);
+            for i in 0..b.rows {
+                for c in 0..b.cols {
+                    let terms = self[i]
+                        .iter()
+                        .enumerate()
+                        .map(|(k, a)| (*a, x[k][c]))
+                        .chain(std::iter::once((-1.0, b[i][c])));
+                    r[i][c] = -compensated_dot(terms);
+                }
+            }
+            let d = lu.solve(&r)?;
+            let x_norm = x.data.iter().fold(0.0_f64, |acc, v| acc.max(v.abs()));
+            let d_norm = d.data.iter().fold(0.0_f64, |acc, v| acc.max(v.abs()));
+            x.data
+                .iter_mut()
+                .zip(d.data.iter())
+                .for_each(|(xi, di)| *xi += di);
+            if d_norm <= f64::EPSILON * x_norm {
+                return Ok(x);
+            }
+        }
+        Err(MatrixError::NoConvergence {
+            iterations: max_iter,
+        })
+    }
+
+    /// Whether the matrix is square with a determinant that is not negligible
+    /// next to Hadamard's bound, the product of the row norms. Both sides are
+    /// compared in log space, so uniformly scaling the matrix does not change the
+    /// answer. A 0x0 matrix is invertible, since its determinant is 1.
+    pub fn is_invertible(&self) -> bool {
+        if self.rows != self.cols {
+            return false;
+        }
+        let Ok(lu) = Lu::new(self) else {
+            return false;
+        };
+        let log_bound: f64 = (0..self.rows)
+            .map(|r| self[r].iter().fold(0.0_f64, |acc, x| acc.hypot(*x)).ln())
+            .sum();
+        lu.log_abs_det() > f64::EPSILON.ln() + log_bound
+    }
+
+    /// Whether the matrix is square with `|det| > tol`.
+    pub fn is_invertible_with_tol(&self, tol: f64) -> bool {
+        self.rows == self.cols && self.det().abs() > tol
+    }
+
+    /// Product of the main diagonal entries. For a triangular matrix this is the
+    /// determinant. Non-square matrices use the leading `min(rows, cols)` entries.
+    pub fn diag_prod(&self) -> f64 {
+        (0..self.rows.min(self.cols)).map(|i| self[i][i]).product()
+    }
+
+    /// Sum of the leading `min(rows, cols)` diagonal entries. See `trace`.
+    pub fn diag_sum(&self) -> f64 {
+        (0..self.rows.min(self.cols)).map(|i| self[i][i]).sum()
+    }
+
+    /// Exchanges rows `r1` and `r2` in place.
+    pub fn swap_rows(&mut self, r1: usize, r2: usize) {
+        assert!(
+            r1 < self.rows && r2 < self.rows,
+            "Cannot swap rows {r1} and {r2} of a matrix with {} rows.",
+            self.rows
+        );
+        for c in 0..self.cols {
+            self.data.swap(r1 * self.cols + c, r2 * self.cols + c);
+        }
+    }
+
+    /// Snaps every entry within `epsilon` of an integer to that integer, which
+    /// also clears tiny residues and `-0.0`. Useful for tidying the output of
+    /// elimination on matrices with integer entries.
+    pub fn round_near_integers(&mut self, epsilon: f64) {
+        for elem in self.data.iter_mut() {
+            let rounded = elem.round();
+            if (*elem - rounded).abs() <= epsilon {
+                *elem = rounded + 0.0;
+            }
+        }
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting. Entries below a tolerance
+    /// scaled to the largest element are treated as zero. Returns the reduced
+    /// row echelon form and its pivot columns.
+    pub fn rref_with_pivot_info(&self) -> (Self, Vec<usize>) {
+        let mut m = self.clone();
+        let max = m.max_abs_element();
+        let tol = f64::EPSILON * self.rows.max(self.cols) as f64 * max;
+        let mut pivots = Vec::new();
+        let mut lead = 0;
+
+        for c in 0..m.cols {
+            if lead == m.rows {
+                break;
+            }
+            let p = (lead..m.rows)
+                .max_by(|a, b| m[*a][c].abs().total_cmp(&m[*b][c].abs()))
+                .unwrap();
+            if m[p][c].abs() <= tol {
+                for r in lead..m.rows {
+                    m[r][c] = 0.0;
+                }
+                continue;
+            }
+            m.swap_rows(lead, p);
+            let div = m[lead][c];
+            m[lead].iter_mut().for_each(|elem| *elem /= div);
+            for r in 0..m.rows {
+                let mult = m[r][c];
+                if r != lead && mult != 0.0 {
+                    for j in 0..m.cols {
+                        m[r][j] -= mult * m[lead][j];
+                    }
+                }
+            }
+            pivots.push(c);
+            lead += 1;
+        }
+        (m, pivots)
+    }
+
+    /// Frobenius norm: the square root of the sum of squared entries.
+    pub fn norm(&self) -> f64 {
+        self.norm_sq().sqrt()
+    }
+
+    /// Squared Frobenius norm, for comparisons that do not need the `sqrt`.
+    pub fn norm_sq(&self) -> f64 {
+        self.data.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    /// Running sum along each row: entry `(i, j)` is the sum of row `i` up to column `j`.
+    pub fn cumsum_row(&self) -> Self {
+        self.scan_rows(0.0, |acc, x| acc + x)
+    }
+
+    /// Running sum down each column: entry `(i, j)` is the sum of column `j` up to row `i`.
+    pub fn cumsum_col(&self) -> Self {
+        let mut m = self.clone();
+        for r in 1..m.rows {
+            for c in 0..m.cols {
+                m[r][c] += m[r - 1][c];
+            }
+        }
+        m
+    }
+
+    /// Convolves every row with `kernel`, keeping only the positions where the
+    /// kernel fully overlaps the row, so the result has `cols - kernel.len() + 1`
+    /// columns.
+    pub fn conv1d_rows(&self, kernel: &[f64]) -> Result<Self, MatrixError> {
+        self.check_kernel(kernel)?;
+        if kernel.len() > self.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Kernel of length {} is longer than the {} columns.",
+                kernel.len(),
+                self.cols
+            )));
+        }
+        Ok(self.conv1d_rows_window(kernel, kernel.len() - 1, self.cols - kernel.len() + 1))
+    }
+
+    /// Convolves every row with `kernel`, zero-padding so the result keeps the
+    /// input width. The output is centered like NumPy's `mode="same"`.
+    pub fn conv1d_rows_same(&self, kernel: &[f64]) -> Result<Self, MatrixError> {
+        self.check_kernel(kernel)?;
+        Ok(self.conv1d_rows_window(kernel, (kernel.len() - 1) / 2, self.cols))
+    }
+
+    /// Copies the matrix out as a list of rows.
+    pub fn to_vec_of_rows(&self) -> Vec<Vec<f64>> {
+        (0..self.rows).map(|r| self[r].to_vec()).collect()
+    }
+
+    /// Copies the matrix out as a list of columns.
+    pub fn to_vec_of_cols(&self) -> Vec<Vec<f64>> {
+        (0..self.cols)
+            .map(|c| (0..self.rows).map(|r| self[r][c]).collect())
+            .collect()
+    }
+
+    /// Multiplies every entry of row `r` by `factor`.
+    pub fn scale_row(&mut self, r: usize, factor: f64) -> Result<(), MatrixError> {
+        self.apply_row_inplace(r, |x| x * factor)
+    }
+
+    /// Multiplies every entry of column `c` by `factor`.
+    pub fn scale_col(&mut self, c: usize, factor: f64) -> Result<(), MatrixError> {
+        self.apply_col_inplace(c, |x| x * factor)
+    }
+
+    /// Adds `factor` times row `source` to row `target`.
+    pub fn add_scaled_row(
+        &mut self,
+        target: usize,
+        source: usize,
+        factor: f64,
+    ) -> Result<(), MatrixError> {
+        for index in [target, source] {
+            if index >= self.rows {
+                return Err(MatrixError::IndexOutOfBounds {
+                    index,
+                    len: self.rows,
+                });
+            }
+        }
+        for c in 0..self.cols {
+            self.data[target * self.cols + c] += factor * self.data[source * self.cols + c];
+        }
+        Ok(())
+    }
+
+    /// The augmented matrix `[self | b]`: the columns of `b` appended on the right.
+    pub fn augment(&self, b: &Matrix) -> Result<Self, MatrixError> {
+        if self.rows != b.rows {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Cannot augment a matrix with {} rows by one with {} rows.",
+                self.rows, b.rows
+            )));
+        }
+        let mut data = Vec::with_capacity(self.rows * (self.cols + b.cols));
+        for r in 0..self.rows {
+            data.extend_from_slice(&self[r]);
+            data.extend_from_slice(&b[r]);
+        }
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols + b.cols,
+            data,
+        })
+    }
+
+    /// Reads the solution of `A x = b` from the reduced row echelon form of the
+    /// augmented matrix `[A | b]`. Fails with `Inconsistent` if a row reads
+    /// `[0 ... 0 | c]` with `c != 0`, and with `Singular` if some unknown is free.
+    pub fn extract_solution(&self) -> Result<Vec<f64>, MatrixError> {
+        if self.cols == 0 {
+            return Err(MatrixError::DimensionMismatch(
+                "An augmented matrix needs at least one column.".to_string(),
+            ));
+        }
+        let n = self.cols - 1;
+        let max = self.max_abs_element();
+        let tol = f64::EPSILON * self.rows.max(self.cols) as f64 * max;
+        let zero = |x: &f64| x.abs() <= tol;
+
+        if (0..self.rows).any(|r| self[r][..n].iter().all(zero) && !zero(&self[r][n])) {
+            return Err(MatrixError::Inconsistent);
+        }
+        let mut x = vec![None; n];
+        for r in 0..self.rows {
+            let coeffs = &self[r][..n];
+            let Some(p) = coeffs.iter().position(|c| !zero(c)) else {
+                continue;
+            };
+            if (coeffs[p] - 1.0).abs() > tol || x[p].is_some() {
+                return Err(MatrixError::InvalidFormat(
+                    "Matrix is not in reduced row echelon form.".to_string(),
+                ));
+            }
+            if !coeffs[p + 1..].iter().all(zero) {
+                return Err(MatrixError::Singular);
+            }
+            x[p] = Some(self[r][n]);
+        }
+        x.into_iter()
+            .collect::<Option<Vec<f64>>>()
+            .ok_or(MatrixError::Singular)
+    }
+
+    /// `tr(A^k)`. Computes `X = A^(k/2)` and `Y = A^(k - k/2)` by repeated
+    /// squaring and then only the diagonal of `X Y`, so `A^k` is never formed.
+    pub fn trace_power(&self, k: usize) -> Result<f64, MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let x = self.power(k / 2);
+        let y = if k.is_multiple_of(2) {
+            x.clone()
+        } else {
+            x.dot(self)
+        };
+        Ok((0..n)
+            .map(|i| (0..n).map(|j| x[i][j] * y[j][i]).sum::<f64>())
+            .sum())
+    }
+
+    /// `diag(d) * self`: row `i` scaled by `d[i]`, without forming the diagonal matrix.
+    pub fn diagonal_matrix_multiply_left(&self, d: &[f64]) -> Result<Self, MatrixError> {
+        if d.len() != self.rows {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Diagonal has {} entries, expected {}.",
+                d.len(),
+                self.rows
+            )));
+        }
+        let mut m = self.clone();
+        for (r, factor) in d.iter().enumerate() {
+            m[r].iter_mut().for_each(|elem| *elem *= factor);
+        }
+        Ok(m)
+    }
+
+    /// `self * diag(d)`: column `j` scaled by `d[j]`, without forming the diagonal matrix.
+    pub fn diagonal_matrix_multiply_right(&self, d: &[f64]) -> Result<Self, MatrixError> {
+        if d.len() != self.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Diagonal has {} entries, expected {}.",
+                d.len(),
+                self.cols
+            )));
+        }
+        let mut m = self.clone();
+        for r in 0..m.rows {
+            m[r].iter_mut()
+                .zip(d)
+                .for_each(|(elem, factor)| *elem *= factor);
+        }
+        Ok(m)
+    }
+
+    /// `(rows, cols)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Prints the shape, e.g. `Matrix(3×4)`.
+    pub fn print_shape(&self) {
+        println!("Matrix({}×{})", self.rows, self.cols);
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Whether the matrix has no entries, i.e. no rows or no columns.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0 || self.cols == 0
+    }
+
+    /// Running fold along each row: entry `(i, j)` is `f` folded over row `i` up
+    /// to column `j`, starting from `init`.
+    pub fn scan_rows(&self, init: f64, f: impl Fn(f64, f64) -> f64) -> Self {
+        let mut m = self.clone();
+        for r in 0..m.rows {
+            let mut acc = init;
+            for elem in m[r].iter_mut() {
+                acc = f(acc, *elem);
+                *elem = acc;
+            }
+        }
+        m
+    }
+
+    /// QR factorization with column pivoting, `A P = Q R`, by Householder
+    /// reflections. At each step the remaining column with the largest norm is
+    /// moved forward, so `|R[0][0]| >= |R[1][1]| >= ...` and the rank shows up as
+    /// the point where the diagonal drops below a tolerance. `Q` is m x m, `R` is
+    /// m x n, and `perm[j]` is the column of `A` moved to column `j`.
+    pub fn col_pivot_qr(&self) -> Result<(Self, Self, Vec<usize>), MatrixError> {
+        let (m, n) = (self.rows, self.cols);
+        let mut q = Self::eye(m);
+        let mut r = self.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+
+        for k in 0..m.min(n) {
+            let col_norm = |r: &Self, j: usize| (k..m).map(|i| r[i][j] * r[i][j]).sum::<f64>();
+            let p = (k..n)
+                .max_by(|a, b| col_norm(&r, *a).total_cmp(&col_norm(&r, *b)))
+                .unwrap();
+            if p != k {
+                for i in 0..m {
+                    r.data.swap(i * n + k, i * n + p);
+                }
+                perm.swap(k, p);
+            }
+            let x: Vec<f64> = (k..m).map(|i| r[i][k]).collect();
+            if let Some(v) = householder_vector(&x) {
+                r.reflect_rows(&v, k);
+                q.reflect_cols(&v, k);
+                for i in k + 1..m {
+                    r[i][k] = 0.0;
+                }
+            }
+        }
+        Ok((q, r, perm))
+    }
+
+    /// Nonnegative matrix factorization `A ~ W H` with `W` (m x k) and `H`
+    /// (k x n) nonnegative, by Lee and Seung's multiplicative updates from a
+    /// random start. Stops once the relative drop in `||A - W H||_F` between
+    /// iterations is below `tol`, or after `max_iter` iterations.
+    pub fn nmf(&self, k: usize, max_iter: usize, tol: f64) -> Result<(Self, Self), MatrixError> {
+        self.nmf_with_rng(k, max_iter, tol, &mut rand::thread_rng())
+    }
+
+    /// `nmf` with the random starting point drawn from `rng`.
+    pub fn nmf_with_rng(
+        &self,
+        k: usize,
+        max_iter: usize,
+        tol: f64,
+        rng: &mut impl Rng,
+    ) -> Result<(Self, Self), MatrixError> {
+        if k == 0 {
+            return Err(MatrixError::InvalidParameter(
+                "Rank k must be at least 1.".to_string(),
+            ));
+        }
+        if self.data.iter().any(|x| *x < 0.0 || !x.is_finite()) {
+            return Err(MatrixError::InvalidParameter(
+                "NMF requires finite, nonnegative entries.".to_string(),
+            ));
+        }
+        // Keeps the multiplicative updates from dividing by zero.
+        const GUARD: f64 = 1e-12;
+        let (m, n) = (self.rows, self.cols);
+        let mean = self.data.iter().sum::<f64>() / (m * n).max(1) as f64;
+        let scale = (mean / k as f64).sqrt();
+        let mut w = Self::new(m, k);
+        let mut h = Self::new(k, n);
+        w.data
+            .iter_mut()
+            .for_each(|x| *x = scale * rng.gen::<f64>());
+        h.data
+            .iter_mut()
+            .for_each(|x| *x = scale * rng.gen::<f64>());
+
+        let mut err = f64::INFINITY;
+        for _ in 0..max_iter {
+            let wt = w.transpose();
+            let numer = wt.dot(self);
+            let denom = wt.dot(&w).dot(&h);
+            for ((x, a), b) in h.data.iter_mut().zip(&numer.data).zip(&denom.data) {
+                *x *= a / (b + GUARD);
+            }
+            let ht = h.transpose();
+            let numer = self.dot(&ht);
+            let denom = w.dot(&h.dot(&ht));
+            for ((x, a), b) in w.data.iter_mut().zip(&numer.data).zip(&denom.data) {
+                *x *= a / (b + GUARD);
+            }
+
+            let next = self.zip_apply(&w.dot(&h), |a, b| a - b).map(|r| r.norm())?;
+            if err.is_finite() && err - next <= tol * err {
+                break;
+            }
+            err = next;
+        }
+        Ok((w, h))
+    }
+
+    /// Softmax along `axis`: `0` normalizes each column and `1` each row. Uses
+    /// `exp(x - max) / sum(exp(x - max))` so large entries do not overflow.
+    pub fn softmax(&self, axis: usize) -> Result<Self, MatrixError> {
+        self.map_axis(axis, |x| {
+            let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let exps: Vec<f64> = x.iter().map(|v| (v - max).exp()).collect();
+            let sum: f64 = exps.iter().sum();
+            exps.iter().map(|e| e / sum).collect()
+        })
+    }
+
+    /// Log-softmax along `axis` (`0` columns, `1` rows): `x - log(sum(exp(x)))`,
+    /// evaluated as `x - max - log(sum(exp(x - max)))` so neither the sum nor the
+    /// softmax itself is ever formed in a way that can overflow or underflow to 0.
+    pub fn log_softmax(&self, axis: usize) -> Result<Self, MatrixError> {
+        self.map_axis(axis, |x| {
+            let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let log_sum = x.iter().map(|v| (v - max).exp()).sum::<f64>().ln();
+            x.iter().map(|v| v - max - log_sum).collect()
+        })
+    }
+
+    /// Element-wise `max(0, x)`.
+    pub fn relu(&self) -> Self {
+        self.apply(|x| x.max(0.0))
+    }
+
+    /// Element-wise `x` for `x >= 0` and `alpha * x` otherwise.
+    pub fn leaky_relu(&self, alpha: f64) -> Self {
+        self.apply(|x| if x >= 0.0 { x } else { alpha * x })
+    }
+
+    /// Element-wise logistic function `1 / (1 + exp(-x))`.
+    pub fn sigmoid(&self) -> Self {
+        self.apply(|x| 1.0 / (1.0 + (-x).exp()))
+    }
+
+    /// Element-wise (Hadamard) product.
+    pub fn hadamard(&self, b: &Matrix) -> Result<Self, MatrixError> {
+        self.zip_apply(b, |x, y| x * y)
+    }
+
+    /// Frobenius inner product `tr(A^T B)`, the sum of `A[i][j] * B[i][j]`.
+    pub fn frobenius_inner_product(&self, b: &Matrix) -> Result<f64, MatrixError> {
+        if self.rows != b.rows || self.cols != b.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Matrices must be of the same size. M1 is {} by {}, M2 is {} by {}.",
+                self.rows, self.cols, b.rows, b.cols
+            )));
+        }
+        Ok(self.data.iter().zip(&b.data).map(|(x, y)| x * y).sum())
+    }
+
+    /// Iterates, in row-major order, over the entries at `(r * row_stride,
+    /// c * col_stride)` that lie inside the matrix, without copying. Both strides
+    /// must be positive.
+    pub fn strided_iter(
+        &self,
+        row_stride: usize,
+        col_stride: usize,
+    ) -> Result<impl Iterator<Item = f64> + '_, MatrixError> {
+        if row_stride == 0 || col_stride == 0 {
+            return Err(MatrixError::InvalidParameter(format!(
+                "Strides must be positive, got ({row_stride}, {col_stride})."
+            )));
+        }
+        Ok((0..self.rows)
+            .step_by(row_stride)
+            .flat_map(move |r| self[r].iter().step_by(col_stride).copied()))
+    }
+
+    /// `rows` copies of `v` stacked as the rows of a `rows x v.len()` matrix.
+    pub fn repeat_vector(v: &[f64], rows: usize) -> Self {
+        Self {
+            rows,
+            cols: v.len(),
+            data: v.repeat(rows),
+        }
+    }
+
+    /// `cols` copies of `v` side by side as the columns of a `v.len() x cols` matrix.
+    pub fn repeat_col_vector(v: &[f64], cols: usize) -> Self {
+        Self {
+            rows: v.len(),
+            cols,
+            data: v
+                .iter()
+                .flat_map(|x| std::iter::repeat_n(*x, cols))
+                .collect(),
+        }
+    }
+
+    /// Whether no entry is NaN.
+    pub fn is_nan_free(&self) -> bool {
+        self.data.iter().all(|x| !x.is_nan())
+    }
+
+    /// Whether every entry is finite, i.e. neither NaN nor infinite.
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|x| x.is_finite())
+    }
+
+    /// Replaces every NaN entry with `value`.
+    pub fn replace_nan(&mut self, value: f64) {
+        self.data
+            .iter_mut()
+            .filter(|x| x.is_nan())
+            .for_each(|x| *x = value);
+    }
+
+    /// Returns a copy with every entry whose row-major position is `true` in
+    /// `mask` set to `value`.
+    pub fn mask_apply(&self, mask: &[bool], value: f64) -> Result<Self, MatrixError> {
+        if mask.len() != self.data.len() {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Mask has {} entries, expected {}.",
+                mask.len(),
+                self.data.len()
+            )));
+        }
+        let mut m = self.clone();
+        for (elem, _) in m.data.iter_mut().zip(mask).filter(|(_, set)| **set) {
+            *elem = value;
+        }
+        Ok(m)
+    }
+
+    /// Like NumPy's `where`: `true_val` where `condition` holds, `false_val` elsewhere.
+    pub fn where_(&self, condition: impl Fn(f64) -> bool, true_val: f64, false_val: f64) -> Self {
+        self.apply(|x| if condition(x) { true_val } else { false_val })
+    }
+
+    /// The `u.len() x v.len()` matrix with entries `u[i] + v[j]`.
+    pub fn outer_sum(u: &[f64], v: &[f64]) -> Self {
+        Self {
+            rows: u.len(),
+            cols: v.len(),
+            data: u
+                .iter()
+                .flat_map(|a| v.iter().map(move |b| a + b))
+                .collect(),
+        }
+    }
+
+    /// Pairwise Euclidean distances between the rows of `x` and the rows of `y`,
+    /// from `||x - y||^2 = ||x||^2 + ||y||^2 - 2 x . y` so the bulk of the work
+    /// is one matrix product. Rounding can push tiny squared distances below
+    /// zero; those are clamped to zero.
+    pub fn distance_matrix(x: &Matrix, y: &Matrix) -> Result<Self, MatrixError> {
+        if x.cols != y.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Points in x have {} coordinates but points in y have {}.",
+                x.cols, y.cols
+            )));
+        }
+        let mut d = Self::squared_distances(x, y);
+        d.apply_inplace(f64::sqrt);
+        Ok(d)
+    }
+
+    /// Gaussian RBF kernel matrix `K[i][j] = exp(-gamma ||x[i] - x[j]||^2)` over
+    /// the rows of `x`. The result is symmetric positive semidefinite.
+    pub fn rbf_kernel(x: &Matrix, gamma: f64) -> Self {
+        let mut k = Self::squared_distances(x, x);
+        k.apply_inplace(|d| (-gamma * d).exp());
+        k
+    }
+
+    /// Largest absolute value among the entries, or 0.0 for an empty matrix.
+    /// NaN entries are ignored.
+    pub fn max_abs_element(&self) -> f64 {
+        self.data.iter().fold(0.0_f64, |acc, x| acc.max(x.abs()))
+    }
+
+    /// Smallest nonzero absolute value among the entries, or `None` if every
+    /// entry is zero or the matrix is empty. NaN entries are ignored.
+    pub fn min_abs_nonzero(&self) -> Option<f64> {
+        self.data
+            .iter()
+            .map(|x| x.abs())
+            .filter(|x| *x > 0.0)
+            .reduce(f64::min)
+    }
+
+    /// Evaluates the polynomial `coeffs[0] A^d + ... + coeffs[d-1] A + coeffs[d] I`
+    /// at `self` by Horner's method. Coefficients run from the highest degree
+    /// down, as in NumPy's `polyval`; an empty slice gives the zero matrix.
+    pub fn polyval(&self, coeffs: &[f64]) -> Result<Self, MatrixError> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut p = Self::new(n, n);
+        for (k, c) in coeffs.iter().enumerate() {
+            if k > 0 {
+                p = self.dot(&p);
+            }
+            for i in 0..n {
+                p[i][i] += c;
+            }
+        }
+        Ok(p)
+    }
+
+    /// Matrix exponential `e^A` by scaling and squaring with a degree 6 Padé
+    /// approximant: `A` is scaled by `2^-s` until its norm is at most 1/2, the
+    /// approximant `D(X)^-1 N(X)` is evaluated and the result squared `s` times.
+    pub fn exp_matrix(&self) -> Result<Self, MatrixError> {
+        self.check_square()?;
+        if !self.is_finite() {
+            return Err(MatrixError::InvalidParameter(
+                "Matrix exponential needs finite entries.".to_string(),
+            ));
+        }
+        let norm = self.norm();
+        let s = if norm > 0.5 {
+            (norm.log2().ceil() as i32 + 1).max(0)
+        } else {
+            0
+        };
+        let x = self.apply(|v| v * 0.5f64.powi(s));
+
+        const Q: usize = 6;
+        let mut c = vec![1.0];
+        for k in 1..=Q {
+            c.push(c[k - 1] * (Q - k + 1) as f64 / (k * (2 * Q - k + 1)) as f64);
+        }
+        let numer: Vec<f64> = c.iter().rev().copied().collect();
+        let denom: Vec<f64> = c
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(k, c)| if k % 2 == 1 { -c } else { *c })
+            .collect();
+        let mut e = x.polyval(&denom)?.solve(&x.polyval(&numer)?)?;
+        for _ in 0..s {
+            e = e.dot(&e);
+        }
+        Ok(e)
+    }
+
+    /// Principal matrix logarithm by inverse scaling and squaring on the real
+    /// Schur form `A = Q T Q^T`: square roots of `T` are taken until it is close
+    /// to `I`, the log of the root comes from the series
+    /// `log R = 2 atanh((R + I)^-1 (R - I))`, and the result is scaled back up.
+    /// Fails if `A` has a real eigenvalue that is zero or negative, since then no
+    /// real logarithm exists.
+    pub fn log_matrix(&self) -> Result<Self, MatrixError> {
+        let (q, t) = self.schur()?;
+        if t.schur_blocks()
+            .iter()
+            .any(|b| b.len() == 1 && t[b.start][b.start] <= 0.0)
+        {
+            return Err(MatrixError::InvalidParameter(
+                "Matrix has a non-positive real eigenvalue, so it has no real logarithm."
+                    .to_string(),
+            ));
+        }
+        let n = t.rows;
+        let mut r = t;
+        let mut roots = 0;
+        while r.zip_apply(&Self::eye(n), |x, y| x - y)?.norm() > 0.25 {
+            if roots == 64 {
+                return Err(MatrixError::NoConvergence { iterations: roots });
+            }
+            r = r.sqrt_quasi_triangular()?;
+            roots += 1;
+        }
+
+        let (mut plus, mut minus) = (r.clone(), r);
+        for i in 0..n {
+            plus[i][i] += 1.0;
+            minus[i][i] -= 1.0;
+        }
+        let z = plus.solve(&minus)?;
+        let z2 = z.dot(&z);
+        let mut term = z.clone();
+        let mut sum = z;
+        for k in 1..=20 {
+            term = term.dot(&z2);
+            let denom = (2 * k + 1) as f64;
+            sum.data
+                .iter_mut()
+                .zip(&term.data)
+                .for_each(|(s, t)| *s += t / denom);
+            if term.max_abs_element() <= f64::EPSILON * sum.max_abs_element() {
+                break;
+            }
+        }
+        let log_t = sum.apply(|x| x * 2f64.powi(roots as i32 + 1));
+        Ok(q.dot(&log_t).dot(&q.transpose()))
+    }
+
+    /// Principal square root `S` with `S^2 = A`, whose eigenvalues all have
+    /// nonnegative real part. It applies Higham's recurrence to the real Schur
+    /// form `A = Q T Q^T`. Fails if `A` has a negative real eigenvalue, or if a
+    /// repeated zero eigenvalue leaves the recurrence singular.
+    pub fn sqrt_matrix(&self) -> Result<Self, MatrixError> {
+        let (q, t) = self.schur()?;
+        let r = t.sqrt_quasi_triangular()?;
+        Ok(q.dot(&r).dot(&q.transpose()))
+    }
+
+    /// Bilinear form `x^T A y`, accumulated row by row without building `A y`.
+    pub fn bilinear_form(x: &[f64], a: &Matrix, y: &[f64]) -> Result<f64, MatrixError> {
+        if x.len() != a.rows || y.len() != a.cols {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "A is {} by {}, x has {} entries and y has {}.",
+                a.rows,
+                a.cols,
+                x.len(),
+                y.len()
+            )));
+        }
+        Ok(x.iter()
+            .enumerate()
+            .map(|(i, xi)| xi * a[i].iter().zip(y).map(|(aij, yj)| aij * yj).sum::<f64>())
+            .sum())
+    }
+
+    /// Rayleigh quotient `v^T A v / v^T v`. For symmetric `A` it lies between
+    /// the smallest and largest eigenvalues, and equals the eigenvalue when `v`
+    /// is an eigenvector.
+    pub fn rayleigh_quotient(&self, v: &[f64]) -> Result<f64, MatrixError> {
+        self.check_square()?;
+        let vtv: f64 = v.iter().map(|x| x * x).sum();
+        let vtav = Self::bilinear_form(v, self, v)?;
+        if vtv == 0.0 {
+            return Err(MatrixError::InvalidParameter(
+                "Rayleigh quotient needs a nonzero vector.".to_string(),
+            ));
+        }
+        Ok(vtav / vtv)
+    }
+
+    /// Solves the Sylvester equation `A X + X B = C` by the Bartels-Stewart
+    /// algorithm: with real Schur forms `A = U S U^T` and `B = V T V^T` it
+    /// becomes `S Y + Y T = U^T C V`, which is solved block by block from the
+    /// bottom left, and `X = U Y V^T`. `A` is m x m, `B` is n x n and `C` is
+    /// m x n. A unique solution exists unless `A` and `-B` share an eigenvalue,
+    /// in which case this returns `Singular`.
+    pub fn sylvester_solve(a: &Matrix, b: &Matrix, c: &Matrix) -> Result<Self, MatrixError> {
+        a.check_square()?;
+        b.check_square()?;
+        if c.rows != a.rows || c.cols != b.rows {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "C is {} by {}, expected {} by {}.",
+                c.rows, c.cols, a.rows, b.rows
+            )));
+        }
+        let (u, s) = a.schur()?;
+        let (v, t) = b.schur()?;
+        let f = u.transpose().dot(c).dot(&v);
+        let (row_blocks, col_blocks) = (s.schur_blocks(), t.schur_blocks());
+
+        let mut y = Self::new(c.rows, c.cols);
+        for bj in &col_blocks {
+            for bi in row_blocks.iter().rev() {
+                let mut rhs = f.block(bi.clone(), bj.clone());
+                for bk in row_blocks.iter().filter(|bk| bk.start > bi.start) {
+                    let p = s
+                        .block(bi.clone(), bk.clone())
+                        .dot(&y.block(bk.clone(), bj.clone()));
+                    rhs.data.iter_mut().zip(&p.data).for_each(|(x, p)| *x -= p);
+                }
+                for bl in col_blocks.iter().filter(|bl| bl.start < bj.start) {
+                    let p = y
+                        .block(bi.clone(), bl.clone())
+                        .dot(&t.block(bl.clone(), bj.clone()));
+                    rhs.data.iter_mut().zip(&p.data).for_each(|(x, p)| *x -= p);
+                }
+                let x = Self::small_sylvester(
+                    &s.block(bi.clone(), bi.clone()),
+                    &t.block(bj.clone(), bj.clone()),
+                    &rhs,
+                )?;
+                y.set_block(bi.start, bj.start, &x);
+            }
+        }
+        Ok(u.dot(&y).dot(&v.transpose()))
+    }
+
+    /// Solves the discrete Lyapunov equation `A X A^T - X + Q = 0`, where `Q`
+    /// must be symmetric positive semidefinite. The Cayley transform
+    /// `A_c = (A - I)(A + I)^-1` turns it into the continuous equation
+    /// `A_c X + X A_c^T = -2 (A + I)^-1 Q (A + I)^-T`, which `sylvester_solve`
+    /// handles. The solution is unique unless two eigenvalues of `A` multiply to
+    /// 1, in which case this returns `Singular`. When the spectral radius of `A`
+    /// is below 1, `X = sum_k A^k Q (A^T)^k` is the steady-state covariance.
+    pub fn lyapunov_solve(a: &Matrix, q: &Matrix) -> Result<Self, MatrixError> {
+        a.check_square()?;
+        q.check_square()?;
+        let n = a.rows;
+        if q.rows != n {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Q is {} by {}, expected {} by {}.",
+                q.rows, q.cols, n, n
+            )));
+        }
+        let tol = f64::EPSILON * n as f64 * q.max_abs_element();
+        if !q.is_symmetric(tol) || q.symmetric_eigen()?.0.iter().any(|v| *v < -tol) {
+            return Err(MatrixError::InvalidParameter(
+                "Q must be symmetric positive semidefinite.".to_string(),
+            ));
+        }
+
+        let mut plus = a.clone();
+        let mut minus = a.clone();
+        for i in 0..n {
+            plus[i][i] += 1.0;
+            minus[i][i] -= 1.0;
+        }
+        // (A - I)(A + I)^-1 = ((A + I)^-T (A - I)^T)^T
+        let ac = plus.transpose().solve(&minus.transpose())?.transpose();
+        let w = plus.solve(q)?;
+        let qc = plus.solve(&w.transpose())?.apply(|x| -2.0 * x);
+        let x = Self::sylvester_solve(&ac, &ac.transpose(), &qc)?;
+        // Symmetrize away rounding.
+        let mut sym = x.clone();
+        for i in 0..n {
+            for j in 0..n {
+                sym[i][j] = 0.5 * (x[i][j] + x[j][i]);
+            }
+        }
+        Ok(sym)
+    }
+
+    fn check_square(&self) -> Result<(), MatrixError> {
+        if !self.is_square() {
+            return Err(MatrixError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        Ok(())
+    }
+
+    fn is_symmetric(&self, tol: f64) -> bool {
+        self.rows == self.cols
+            && (0..self.rows).all(|i| (0..i).all(|j| (self[i][j] - self[j][i]).abs() <= tol))
+    }
+
+    /// Eigenvalues (ascending) and orthonormal eigenvectors (as columns) of a
+    /// symmetric matrix, by cyclic Jacobi rotations.
+    fn symmetric_eigen(&self) -> Result<(Vec<f64>, Self), MatrixError> {
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut v = Self::eye(n);
+        let scale = a.norm();
+
+        let mut converged = false;
+        for _ in 0..MAX_ITER {
+            let off: f64 = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .filter(|j| *j != i)
+                        .map(|j| a[i][j] * a[i][j])
+                        .sum::<f64>()
+                })
+                .sum();
+            if off.sqrt() <= f64::EPSILON * scale {
+                converged = true;
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    if a[p][q] == 0.0 {
+                        continue;
+                    }
+                    let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    a.rotate_cols(p, q, c, s);
+                    a.rotate_rows(p, q, c, s);
+                    v.rotate_cols(p, q, c, s);
+                }
+            }
+        }
+        if !converged {
+            return Err(MatrixError::NoConvergence {
+                iterations: MAX_ITER,
+            });
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|x, y| a[*x][*x].total_cmp(&a[*y][*y]));
+        let values = order.iter().map(|i| a[*i][*i]).collect();
+        let mut vectors = Self::new(n, n);
+        for (j, k) in order.iter().enumerate() {
+            for i in 0..n {
+                vectors[i][j] = v[i][*k];
+            }
+        }
+        Ok((values, vectors))
+    }
+
+    fn rank(&self) -> usize {
+        self.rref_with_pivot_info().1.len()
+    }
+
+    /// Splits `self` (m x n, rank r) into `B` (m x r) and `C` (r x n) with
+    /// `self = B C`: `B` holds the pivot columns and `C` the nonzero RREF rows.
+    fn rank_factorization(&self) -> (Self, Self) {
+        let (rref, pivots) = self.rref_with_pivot_info();
+        let r = pivots.len();
+        let mut b = Self::new(self.rows, r);
+        for i in 0..self.rows {
+            for (j, p) in pivots.iter().enumerate() {
+                b[i][j] = self[i][*p];
+            }
+        }
+        let c = Self {
+            rows: r,
+            cols: self.cols,
+            data: rref.data[..r * self.cols].to_vec(),
+        };
+        (b, c)
+    }
+
+    /// Applies the Givens rotation `[c s; -s c]` to columns `p` and `q`.
+    fn rotate_cols(&mut self, p: usize, q: usize, c: f64, s: f64) {
+        for i in 0..self.rows {
+            let (x, y) = (self[i][p], self[i][q]);
+            self[i][p] = c * x - s * y;
+            self[i][q] = s * x + c * y;
+        }
+    }
+
+    /// Applies the transpose of the Givens rotation `[c s; -s c]` to rows `p` and `q`.
+    fn rotate_rows(&mut self, p: usize, q: usize, c: f64, s: f64) {
+        for j in 0..self.cols {
+            let (x, y) = (self[p][j], self[q][j]);
+            self[p][j] = c * x - s * y;
+            self[q][j] = s * x + c * y;
+        }
+    }
+
+    /// Modified Gram-Schmidt, run twice for stability. Columns that are
+    /// numerically dependent on earlier ones are set to zero.
+    fn orthonormalize_cols(&mut self) {
+        let scale = self.max_abs_element();
+        let tol = f64::EPSILON * self.rows.max(1) as f64 * scale.max(1.0);
+        for j in 0..self.cols {
+            for _ in 0..2 {
+                for k in 0..j {
+                    let proj: f64 = (0..self.rows).map(|i| self[i][j] * self[i][k]).sum();
+                    for i in 0..self.rows {
+                        self[i][j] -= proj * self[i][k];
+                    }
+                }
+            }
+            let norm = (0..self.rows)
+                .map(|i| self[i][j] * self[i][j])
+                .sum::<f64>()
+                .sqrt();
+            for i in 0..self.rows {
+                self[i][j] = if norm > tol { self[i][j] / norm } else { 0.0 };
+            }
+        }
+    }
+
+    /// Replaces zero columns with unit vectors orthogonal to the other columns,
+    /// so a matrix with some orthonormal and some zero columns ends up with
+    /// orthonormal columns throughout.
+    fn complete_orthonormal_cols(&mut self) {
+        let mut basis = 0;
+        for j in 0..self.cols {
+            while (0..self.rows).all(|i| self[i][j] == 0.0) && basis < self.rows {
+                for i in 0..self.rows {
+                    self[i][j] = if i == basis { 1.0 } else { 0.0 };
+                }
+                basis += 1;
+                for _ in 0..2 {
+                    for k in (0..self.cols).filter(|k| *k != j) {
+                        let proj: f64 = (0..self.rows).map(|i| self[i][j] * self[i][k]).sum();
+                        for i in 0..self.rows {
+                            self[i][j] -= proj * self[i][k];
+                        }
+                    }
+                }
+                let norm = (0..self.rows)
+                    .map(|i| self[i][j] * self[i][j])
+                    .sum::<f64>()
+                    .sqrt();
+                for i in 0..self.rows {
+                    self[i][j] = if norm > 1e-8 { self[i][j] / norm } else { 0.0 };
+                }
+            }
+        }
+    }
+
+    /// Applies `I - 2 v v^T / (v^T v)` from the left to rows `offset..offset + v.len()`.
+    fn reflect_rows(&mut self, v: &[f64], offset: usize) {
+        let vtv: f64 = v.iter().map(|x| x * x).sum();
+        for j in 0..self.cols {
+            let dot: f64 = v
+                .iter()
+                .enumerate()
+                .map(|(i, vi)| vi * self[offset + i][j])
+                .sum();
+            let scale = 2.0 * dot / vtv;
+            for (i, vi) in v.iter().enumerate() {
+                self[offset + i][j] -= scale * vi;
+            }
+        }
+    }
+
+    /// Applies `I - 2 v v^T / (v^T v)` from the right to columns `offset..offset + v.len()`.
+    fn reflect_cols(&mut self, v: &[f64], offset: usize) {
+        let vtv: f64 = v.iter().map(|x| x * x).sum();
+        for i in 0..self.rows {
+            let dot: f64 = v
+                .iter()
+                .enumerate()
+                .map(|(j, vj)| vj * self[i][offset + j])
+                .sum();
+            let scale = 2.0 * dot / vtv;
+            for (j, vj) in v.iter().enumerate() {
+                self[i][offset + j] -= scale * vj;
+            }
+        }
+    }
+
+    fn first_cols(&self, k: usize) -> Self {
+        let mut m = Self::new(self.rows, k);
+        for i in 0..self.rows {
+            m[i].copy_from_slice(&self[i][..k]);
+        }
+        m
+    }
+
+    fn check_kernel(&self, kernel: &[f64]) -> Result<(), MatrixError> {
+        if kernel.is_empty() {
+            return Err(MatrixError::InvalidParameter(
+                "Kernel must not be empty.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Columns `offset..offset + width` of the full convolution of each row
+    /// with `kernel`, treating entries outside the row as zero.
+    fn conv1d_rows_window(&self, kernel: &[f64], offset: usize, width: usize) -> Self {
+        let mut out = Self::new(self.rows, width);
+        for r in 0..self.rows {
+            for j in 0..width {
+                let n = j + offset;
+                out[r][j] = kernel
+                    .iter()
+                    .enumerate()
+                    .filter(|&(k, _)| k <= n && n - k < self.cols)
+                    .map(|(k, h)| h * self[r][n - k])
+                    .sum();
+            }
+        }
+        out
+    }
+
+    /// `A^k` for a square matrix by repeated squaring.
+    fn power(&self, mut k: usize) -> Self {
+        let mut result = Self::eye(self.rows);
+        let mut base = self.clone();
+        while k > 0 {
+            if k % 2 == 1 {
+                result = result.dot(&base);
+            }
+            k /= 2;
+            if k > 0 {
+                base = base.dot(&base);
+            }
+        }
+        result
+    }
+
+    /// `map_cols` for `axis == 0` and `map_rows` for `axis == 1`.
+    fn map_axis(&self, axis: usize, f: impl Fn(&[f64]) -> Vec<f64>) -> Result<Self, MatrixError> {
+        match axis {
+            0 => self.map_cols(f),
+            1 => self.map_rows(f),
+            _ => Err(MatrixError::InvalidParameter(format!(
+                "Axis must be 0 (columns) or 1 (rows), got {axis}."
+            ))),
+        }
+    }
+
+    /// Squared distances between the rows of `x` and `y`, which must have the
+    /// same number of columns, clamped at zero.
+    fn squared_distances(x: &Matrix, y: &Matrix) -> Self {
+        let x_sq = x.fold_rows(0.0, |acc, v| acc + v * v);
+        let y_sq = y.fold_rows(0.0, |acc, v| acc + v * v);
+        let cross = x.dot(&y.transpose());
+        let mut d = Self::outer_sum(&x_sq, &y_sq);
+        for (sq, xy) in d.data.iter_mut().zip(&cross.data) {
+            *sq = (*sq - 2.0 * xy).max(0.0);
+        }
+        d
+    }
+
+    fn eye(n: usize) -> Self {
+        let mut m = Self::new(n, n);
+        m.identity();
+        m
+    }
+}
+
+/// Householder vector `v` with `(I - 2 v v^T / v^T v) x = -sign(x[0]) ||x|| e_1`,
+/// or `None` when `x` is already a multiple of `e_1`. `x` is scaled by its
+/// largest entry first so tiny or huge entries do not underflow or overflow.
+fn householder_vector(x: &[f64]) -> Option<Vec<f64>> {
+    let scale = x.iter().fold(0.0_f64, |acc, a| acc.max(a.abs()));
+    if scale == 0.0 || x[1..].iter().all(|a| *a == 0.0) {
+        return None;
+    }
+    let mut v: Vec<f64> = x.iter().map(|a| a / scale).collect();
+    let norm = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+    v[0] += if v[0] >= 0.0 { norm } else { -norm };
+    Some(v)
+}
+
+/// Sum of products `a * b` in twice the working precision (Ogita, Rump and
+/// Oishi's `Dot2`): each product's rounding error is recovered with an FMA and
+/// each addition's with TwoSum, and the errors are added back at the end.
+fn compensated_dot(terms: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let mut sum = 0.0;
+    let mut err = 0.0;
+    for (a, b) in terms {
+        let p = a * b;
+        let p_err = a.mul_add(b, -p);
+        let s = sum + p;
+        let z = s - sum;
+        err += (sum - (s - z)) + (p - z) + p_err;
+        sum = s;
+    }
+    sum + err
+}
+
+/// Standard normal sample via the Box-Muller transform.
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+impl Display for Matrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.rows == 0 {
+            return writeln!(f, "[]");
+        }
+        for r in 0..self.rows {
+            write!(f, "[")?;
+            for c in 0..self.cols {
+                if c > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{:.3}", self[r][c])?;
+            }
+            writeln!(f, "]")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The empty 0×0 matrix.
+///
+/// By convention its `det` is 1.0 and its `trace` is 0.0, it displays as `[]`,
+/// and `dot`, `transpose`, `inverse`, `rref` and `identity` return empty
+/// matrices. `minor` and `cofactor` panic, since there is no entry to remove.
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+/// Matrices are equal when their shapes match and every entry has the same bit
+/// pattern, so `NaN == NaN` and `0.0 != -0.0`. This keeps `Eq` and `Hash`
+/// consistent with each other.
+impl PartialEq for Matrix {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+            && self.cols == other.cols
+            && self.data.len() == other.data.len()
+            && self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+impl Eq for Matrix {}
+
+impl Hash for Matrix {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rows.hash(state);
+        self.cols.hash(state);
+        for elem in &self.data {
+            elem.to_bits().hash(state);
+        }
+    }
+}
+
+impl FromStr for Matrix {
+    type Err = MatrixError;
+
+    /// Parses the `from_string` format: entries separated by whitespace, rows by `;`.
+    /// Leading and trailing `;` are ignored. Input without any `;` is split on
+    /// newlines instead, skipping blank lines.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(';') {
+            let rows: Vec<&str> = s.split(';').collect();
+            let blank = |r: &&str| r.trim().is_empty();
+            let start = rows.iter().position(|r| !blank(r)).unwrap_or(rows.len());
+            let end = rows
+                .iter()
+                .rposition(|r| !blank(r))
+                .map_or(start, |e| e + 1);
+            Self::parse_rows(rows[start..end].iter().copied())
+        } else {
+            Self::parse_rows(s.lines().filter(|l| !l.trim().is_empty()))
+        }
+    }
+}
+
+impl Index<usize> for Matrix {
+    type Output = [f64];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index * self.cols..(index + 1) * self.cols]
+    }
+}
+
+impl IndexMut<usize> for Matrix {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index * self.cols..(index + 1) * self.cols]
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_from_string() {
-        let m = Matrix::from_string("1 2 3 ; 4 5 6");
-        let expected = Matrix {
-            rows: 2,
-            cols: 3,
-            data: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+    fn test_from_string() {
+        let m = Matrix::from_string("1 2 3 ; 4 5 6");
+        let expected = Matrix {
+            rows: 2,
+            cols: 3,
+            data: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        };
+
+        assert!(m == expected);
+    }
+
+    #[test]
+    fn test_display() {
+        let m = Matrix::from_string("1 2 3 ; 4 5 6");
+
+        assert_eq!("[1.000 2.000 3.000]\n[4.000 5.000 6.000]\n", m.to_string())
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+        set.insert(Matrix::from_string("1 2 ; 3 4"));
+        set.insert(Matrix::from_string("1 2 ; 3 4"));
+        set.insert(Matrix::from_string("1 2 3 4"));
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Matrix::from_string("1 2 ; 3 4")));
+    }
+
+    #[test]
+    fn test_eq_uses_bits() {
+        let nan = Matrix::from_string("NaN");
+        let zero = Matrix::from_string("0");
+        let neg_zero = Matrix::from_string("-0");
+
+        assert_eq!(nan, nan.clone());
+        assert_ne!(zero, neg_zero);
+    }
+
+    #[test]
+    fn test_default() {
+        let mut m = Matrix::default();
+
+        assert_eq!((m.rows, m.cols), (0, 0));
+        assert_eq!(m.det(), 1.0);
+        assert_eq!(m.trace(), 0.0);
+        assert_eq!(m.transpose(), Matrix::default());
+        assert_eq!(m.inverse(), Matrix::default());
+        assert_eq!(m.dot(&Matrix::default()), Matrix::default());
+        m.identity();
+        assert_eq!(m, Matrix::default());
+    }
+
+    #[test]
+    fn test_with_diagonal() {
+        let m = Matrix::from_string("1 2 3 ; 4 5 6");
+        let d = m.with_diagonal(&[7.0, 8.0]).unwrap();
+
+        assert_eq!(d, Matrix::from_string("7 2 3 ; 4 8 6"));
+        assert!(matches!(
+            m.with_diagonal(&[1.0, 2.0, 3.0]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_spectral_radius() {
+        let m = Matrix::from_string("2 1 ; 1 2");
+        assert!((m.spectral_radius().unwrap() - 3.0).abs() < 1e-8);
+
+        let rotation = Matrix::from_string("0 -0.5 ; 0.5 0");
+        assert!((rotation.spectral_radius().unwrap() - 0.5).abs() < 1e-8);
+        assert!(rotation.is_convergent().unwrap());
+
+        assert!(!Matrix::from_string("1 0 ; 0 -1.5").is_convergent().unwrap());
+        assert!(Matrix::from_string("1 2 3").spectral_radius().is_err());
+
+        // Eigenvalues 1 and 3; the old fixed start vector was an eigenvector for 1.
+        let m = Matrix::from_string("1.4 -0.8; -0.8 2.6");
+        assert!((m.spectral_radius().unwrap() - 3.0).abs() < 1e-12);
+        assert!(!m.is_convergent().unwrap());
+
+        // A Jordan block defeats power iteration's convergence test.
+        let j = Matrix::from_string("0.9 1; 0 0.9");
+        assert!((j.spectral_radius().unwrap() - 0.9).abs() < 1e-12);
+        assert!(j.is_convergent().unwrap());
+    }
+
+    #[test]
+    fn test_apply_row_col() {
+        let m = Matrix::from_string("1 2 ; 3 4");
+
+        assert_eq!(
+            m.apply_row(1, |x| x * 10.0).unwrap(),
+            Matrix::from_string("1 2 ; 30 40")
+        );
+        assert_eq!(
+            m.apply_col(0, |x| -x).unwrap(),
+            Matrix::from_string("-1 2 ; -3 4")
+        );
+        assert!(matches!(
+            m.apply_row(2, |x| x),
+            Err(MatrixError::IndexOutOfBounds { index: 2, len: 2 })
+        ));
+
+        let mut n = m.clone();
+        n.apply_col_inplace(1, |x| x + 1.0).unwrap();
+        assert_eq!(n, Matrix::from_string("1 3 ; 3 5"));
+    }
+
+    #[test]
+    fn test_map_rows_cols() {
+        let m = Matrix::from_string("1 2 ; 3 4");
+        let reversed = m.map_rows(|row| row.iter().rev().copied().collect());
+        let cumulative = m.map_cols(|col| vec![col[0], col[0] + col[1]]);
+
+        assert_eq!(reversed.unwrap(), Matrix::from_string("2 1 ; 4 3"));
+        assert_eq!(cumulative.unwrap(), Matrix::from_string("1 2 ; 4 6"));
+        assert!(matches!(
+            m.map_rows(|row| row[..1].to_vec()),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_fold_rows_cols() {
+        let m = Matrix::from_string("1 2 3 ; 4 5 6");
+
+        assert_eq!(m.fold_rows(0.0, |acc, x| acc + x), vec![6.0, 15.0]);
+        assert_eq!(m.fold_cols(1.0, |acc, x| acc * x), vec![4.0, 10.0, 18.0]);
+        assert_eq!(m.fold_rows(f64::NEG_INFINITY, f64::max), vec![3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_zip_apply() {
+        let a = Matrix::from_string("1 2 ; 3 4");
+        let b = Matrix::from_string("5 6 ; 7 8");
+
+        assert_eq!(
+            a.zip_apply(&b, |x, y| x * y).unwrap(),
+            Matrix::from_string("5 12 ; 21 32")
+        );
+        assert!(a.zip_apply(&Matrix::new(2, 3), |x, _| x).is_err());
+    }
+
+    #[test]
+    fn test_position_find() {
+        let m = Matrix::from_string("1 2 3 ; 4 NaN 6");
+
+        assert_eq!(m.position(|x| x.is_nan()), Some((1, 1)));
+        assert_eq!(m.position(|x| x > 10.0), None);
+        assert_eq!(m.find(|x| x > 2.0), Some(3.0));
+    }
+
+    #[test]
+    fn test_labeled_table() {
+        let m = Matrix::from_string("1 -20 ; 3 4");
+        let table = m.labeled_table(&["cat", "dog"], &["predicted cat", "dog", "extra"]);
+
+        assert_eq!(
+            table,
+            "    predicted cat     dog\n\
+             cat         1.000 -20.000\n\
+             dog         3.000   4.000\n"
+        );
+        assert_eq!(
+            m.labeled_table(&["a"], &[]),
+            "\na 1.000 -20.000\n  3.000   4.000\n"
+        );
+    }
+
+    fn approx_eq(a: &Matrix, b: &Matrix) -> bool {
+        a.rows == b.rows
+            && a.cols == b.cols
+            && a.data
+                .iter()
+                .zip(b.data.iter())
+                .all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    /// Entrywise comparison relative to `b`, with an absolute floor of `tol` for
+    /// entries of magnitude below 1. Suits matrix functions whose entries span
+    /// several orders of magnitude.
+    fn close_rel(tol: f64, a: &Matrix, b: &Matrix) -> bool {
+        a.rows == b.rows
+            && a.cols == b.cols
+            && a.data
+                .iter()
+                .zip(&b.data)
+                .all(|(x, y)| (x - y).abs() <= tol * y.abs().max(1.0))
+    }
+
+    #[test]
+    fn test_householder() {
+        let h = Matrix::householder(&[1.0, 2.0, 2.0]);
+
+        assert!(approx_eq(&h, &h.transpose()));
+        assert!(approx_eq(&h.dot(&h.transpose()), &Matrix::eye(3)));
+
+        let x = Matrix::from_string("1 ; 2 ; 2");
+        assert!(approx_eq(&h.dot(&x), &Matrix::from_string("-1 ; -2 ; -2")));
+        assert_eq!(Matrix::householder(&[0.0, 0.0]), Matrix::eye(2));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let angle = 0.7;
+        let rotations = [
+            Matrix::rotation_2d(angle),
+            Matrix::rotation_3d_x(angle),
+            Matrix::rotation_3d_y(angle),
+            Matrix::rotation_3d_z(angle),
+            Matrix::rotation_3d_axis(&[1.0, -2.0, 0.5], angle),
+        ];
+        for r in rotations {
+            assert!(approx_eq(&r.dot(&r.transpose()), &Matrix::eye(r.rows)));
+            assert!((r.det() - 1.0).abs() < 1e-9);
+        }
+
+        let quarter = Matrix::rotation_2d(std::f64::consts::FRAC_PI_2);
+        assert!(approx_eq(
+            &quarter.dot(&Matrix::from_string("1 ; 0")),
+            &Matrix::from_string("0 ; 1")
+        ));
+        assert!(approx_eq(
+            &Matrix::rotation_3d_axis(&[0.0, 0.0, 2.0], angle),
+            &Matrix::rotation_3d_z(angle)
+        ));
+    }
+
+    #[test]
+    fn test_projections() {
+        let p = Matrix::projection_onto(&[1.0, 1.0]);
+        assert!(approx_eq(&p, &Matrix::from_string("0.5 0.5 ; 0.5 0.5")));
+        assert!(approx_eq(&p.dot(&p), &p));
+
+        let a = Matrix::from_string("1 0 ; 0 1 ; 0 0");
+        let hat = a.projection_onto_columns();
+        assert!(approx_eq(
+            &hat,
+            &Matrix::from_string("1 0 0 ; 0 1 0 ; 0 0 0")
+        ));
+
+        let b = Matrix::from_string("1 ; 2 ; 3");
+        assert!(approx_eq(
+            &b.projection_onto_columns(),
+            &Matrix::projection_onto(&[1.0, 2.0, 3.0])
+        ));
+    }
+
+    #[test]
+    fn test_centering() {
+        let x = Matrix::from_string("1 ; 2 ; 6");
+        let centered = Matrix::centering(3).dot(&x);
+
+        assert!(approx_eq(&centered, &Matrix::from_string("-2 ; -1 ; 3")));
+        assert_eq!(Matrix::centering(0), Matrix::default());
+    }
+
+    #[test]
+    fn test_drazin_inverse() {
+        let a = Matrix::from_string("2 0 0 ; 0 0 1 ; 0 0 0");
+        let ad = a.drazin_inverse().unwrap();
+        assert!(approx_eq(
+            &ad,
+            &Matrix::from_string("0.5 0 0 ; 0 0 0 ; 0 0 0")
+        ));
+
+        let b = Matrix::from_string("2 1 ; 1 1");
+        assert!(approx_eq(&b.drazin_inverse().unwrap(), &b.inverse()));
+
+        let nilpotent = Matrix::from_string("0 1 ; 0 0");
+        assert_eq!(nilpotent.drazin_inverse().unwrap(), Matrix::new(2, 2));
+
+        // A^D A A^D = A^D, A A^D = A^D A and A^(k+1) A^D = A^k for k >= index.
+        let c = Matrix::from_string("1 1 0 ; 0 0 1 ; 0 0 0");
+        let cd = c.drazin_inverse().unwrap();
+        assert!(approx_eq(&cd.dot(&c).dot(&cd), &cd));
+        assert!(approx_eq(&c.dot(&cd), &cd.dot(&c)));
+        let c2 = c.dot(&c);
+        assert!(approx_eq(&c2.dot(&c).dot(&cd), &c2));
+
+        assert!(Matrix::new(2, 3).drazin_inverse().is_err());
+    }
+
+    #[test]
+    fn test_ilu0() {
+        let tridiagonal = Matrix::from_string("4 1 0 ; 1 4 1 ; 0 1 4");
+        let (l, u) = tridiagonal.ilu0().unwrap();
+        assert!(approx_eq(&l.dot(&u), &tridiagonal));
+
+        let a = Matrix::from_string("4 0 1 ; 1 4 0 ; 1 0 4");
+        let (l, u) = a.ilu0().unwrap();
+        let lu = l.dot(&u);
+        for i in 0..3 {
+            assert_eq!(l[i][i], 1.0);
+            for j in 0..3 {
+                if a[i][j] != 0.0 {
+                    assert!((lu[i][j] - a[i][j]).abs() < 1e-12);
+                } else {
+                    assert_eq!(l[i][j] + u[i][j], 0.0);
+                }
+            }
+        }
+
+        assert!(matches!(
+            Matrix::from_string("0 1 ; 1 0").ilu0(),
+            Err(MatrixError::Singular)
+        ));
+    }
+
+    fn diag(s: &[f64]) -> Matrix {
+        Matrix::eye(s.len()).with_diagonal(s).unwrap()
+    }
+
+    #[test]
+    fn test_svd() {
+        for a in [
+            Matrix::from_string("3 2 2 ; 2 3 -2"),
+            Matrix::from_string("1 2 ; 3 4 ; 5 6 ; 7 8"),
+            Matrix::from_string("1 2 ; 2 4"),
+        ] {
+            let (u, s, v) = a.svd().unwrap();
+            let k = a.rows.min(a.cols);
+
+            assert!(s.windows(2).all(|w| w[0] >= w[1]));
+            assert!(approx_eq(&u.transpose().dot(&u), &Matrix::eye(k)));
+            assert!(approx_eq(&v.transpose().dot(&v), &Matrix::eye(k)));
+            assert!(approx_eq(&u.dot(&diag(&s)).dot(&v.transpose()), &a));
+        }
+
+        let (_, s, _) = Matrix::from_string("3 2 2 ; 2 3 -2").svd().unwrap();
+        assert!((s[0] - 5.0).abs() < 1e-12 && (s[1] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_randomized_svd() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let left = Matrix::from_string("1 0 ; 2 1 ; 0 3 ; 1 1 ; 4 -1 ; 0 2");
+        let right = Matrix::from_string("1 2 0 -1 3 ; 0 1 4 2 -2");
+        let a = left.dot(&right);
+
+        let (u, s, v) = a.randomized_svd(2, 2, &mut rng).unwrap();
+        let (_, exact, _) = a.svd().unwrap();
+
+        assert_eq!((u.rows, u.cols, v.rows, v.cols), (6, 2, 5, 2));
+        assert!((s[0] - exact[0]).abs() < 1e-8 && (s[1] - exact[1]).abs() < 1e-8);
+        assert!(approx_eq(&u.dot(&diag(&s)).dot(&v.transpose()), &a));
+        assert!(a.randomized_svd(6, 0, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_truncated_svd() {
+        let a = Matrix::from_string("4 0 0 ; 0 3 0 ; 0 0 1 ; 0 0 0");
+        let (u, s, v) = a.truncated_svd(2).unwrap();
+        let approx = u.dot(&diag(&s)).dot(&v.transpose());
+
+        assert_eq!(s, vec![4.0, 3.0]);
+        assert!(approx_eq(
+            &approx,
+            &Matrix::from_string("4 0 0 ; 0 3 0 ; 0 0 0 ; 0 0 0")
+        ));
+        assert!(a.truncated_svd(4).is_err());
+    }
+
+    #[test]
+    fn test_arnoldi() {
+        let a = Matrix::from_string("4 1 0 2 ; 1 3 1 0 ; 2 0 5 1 ; 0 1 1 2");
+        let (q, h) = a.arnoldi(&[1.0, 0.0, 0.0, 0.0], 4).unwrap();
+
+        assert!(approx_eq(&q.transpose().dot(&q), &Matrix::eye(4)));
+        assert!(approx_eq(&a.dot(&q), &q.dot(&h)));
+        for i in 2..4 {
+            for j in 0..i - 1 {
+                assert_eq!(h[i][j], 0.0);
+            }
+        }
+
+        let (q, h) = a.arnoldi(&[1.0, 0.0, 0.0, 0.0], 2).unwrap();
+        assert_eq!((q.cols, h.rows), (2, 2));
+
+        let diagonal = Matrix::from_string("2 0 0 ; 0 2 0 ; 0 0 3");
+        let (q, h) = diagonal.arnoldi(&[1.0, 1.0, 0.0], 3).unwrap();
+        assert_eq!((q.cols, h.cols), (1, 1));
+        assert!(approx_eq(&diagonal.dot(&q), &q.dot(&h)));
+
+        assert!(a.arnoldi(&[0.0; 4], 2).is_err());
+        assert!(a.arnoldi(&[1.0; 3], 2).is_err());
+    }
+
+    #[test]
+    fn test_to_hessenberg() {
+        let a = Matrix::from_string("4 1 2 3 ; 2 5 1 0 ; 1 3 6 2 ; 3 0 1 7");
+        let (q, h) = a.to_hessenberg().unwrap();
+
+        assert!(approx_eq(&q.dot(&q.transpose()), &Matrix::eye(4)));
+        assert!(approx_eq(&q.dot(&h).dot(&q.transpose()), &a));
+        for i in 2..4 {
+            for j in 0..i - 1 {
+                assert_eq!(h[i][j], 0.0);
+            }
+        }
+        assert!(Matrix::new(2, 3).to_hessenberg().is_err());
+    }
+
+    #[test]
+    fn test_polar() {
+        let a = Matrix::from_string("2 -1 0 ; 1 3 1 ; 0 1 4");
+        let (u, p) = a.polar().unwrap();
+
+        assert!(approx_eq(&u.dot(&u.transpose()), &Matrix::eye(3)));
+        assert!(approx_eq(&p, &p.transpose()));
+        assert!(approx_eq(&u.dot(&p), &a));
+
+        let noisy = Matrix::rotation_2d(0.3)
+            .zip_apply(&Matrix::from_string("0.01 0 ; 0 -0.02"), |x, y| x + y);
+        let (rotation, _) = noisy.unwrap().polar().unwrap();
+        assert!((rotation.det() - 1.0).abs() < 1e-9);
+
+        assert!(Matrix::new(2, 3).polar().is_err());
+    }
+
+    #[test]
+    fn test_ridge_regression() {
+        let a = Matrix::from_string("1 0 ; 0 1 ; 1 1");
+        let b = Matrix::from_string("1 ; 2 ; 3");
+
+        let exact = a.ridge_regression(&b, 0.0).unwrap();
+        assert!(approx_eq(&exact, &Matrix::from_string("1 ; 2")));
+
+        // (A^T A + I) x = A^T b is [[3, 1], [1, 3]] x = [4, 5].
+        let ridge = a.ridge_regression(&b, 1.0).unwrap();
+        assert!(approx_eq(&ridge, &Matrix::from_string("0.875 ; 1.375")));
+
+        let rank_deficient = Matrix::from_string("1 1 ; 1 1");
+        assert!(rank_deficient.ridge_regression(&b, 0.5).is_err());
+        let b2 = Matrix::from_string("2 ; 2");
+        assert!(rank_deficient.ridge_regression(&b2, 0.5).is_ok());
+        assert!(matches!(
+            a.ridge_regression(&b, -1.0),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_bandwidth() {
+        let m = Matrix::from_string("1 2 0 0 ; 3 4 5 0 ; 0 6 7 8 ; 1e-12 0 9 1");
+
+        assert_eq!(m.bandwidth(0.0), (3, 1));
+        assert_eq!(m.bandwidth(1e-9), (1, 1));
+        assert!(m.is_banded(1, 1, 1e-9));
+        assert!(!m.is_banded(1, 0, 1e-9));
+        assert_eq!(Matrix::eye(3).bandwidth(0.0), (0, 0));
+    }
+
+    #[test]
+    fn test_lanczos() {
+        let a = Matrix::from_string("4 1 0 0 ; 1 3 1 0 ; 0 1 2 1 ; 0 0 1 1");
+        let (ritz, q) = a.lanczos(4, 1e-12).unwrap();
+        let (eigenvalues, _) = a.symmetric_eigen().unwrap();
+
+        assert!(approx_eq(&q.transpose().dot(&q), &Matrix::eye(4)));
+        for (r, e) in ritz.iter().zip(eigenvalues.iter()) {
+            assert!((r - e).abs() < 1e-9);
+        }
+
+        let (ritz, q) = a.lanczos(2, 1e-12).unwrap();
+        assert_eq!((ritz.len(), q.cols), (2, 2));
+        assert!(ritz[1] <= eigenvalues[3] + 1e-12 && ritz[0] >= eigenvalues[0] - 1e-12);
+
+        assert!(Matrix::from_string("1 2 ; 3 4").lanczos(2, 1e-12).is_err());
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let a = Matrix::from_string("2 1 ; 1 2");
+        let (values, vectors) = a.symmetric_eigen().unwrap();
+
+        assert!((values[0] - 1.0).abs() < 1e-12 && (values[1] - 3.0).abs() < 1e-12);
+        assert!(approx_eq(&a.dot(&vectors), &vectors.dot(&diag(&values))));
+    }
+
+    #[test]
+    fn test_condition_estimate_1norm() {
+        // ||A||_1 = 6 and ||A^-1||_1 = 3.5 for A = [[1, 2], [3, 4]].
+        let a = Matrix::from_string("1 2 ; 3 4");
+        assert!((a.condition_estimate_1norm().unwrap() - 21.0).abs() < 1e-9);
+
+        assert!((Matrix::eye(4).condition_estimate_1norm().unwrap() - 1.0).abs() < 1e-12);
+
+        let ill = Matrix::from_string("1 1 ; 1 1.0001");
+        assert!(ill.condition_estimate_1norm().unwrap() > 1e4);
+        assert!(matches!(
+            Matrix::from_string("1 2 ; 2 4").condition_estimate_1norm(),
+            Err(MatrixError::Singular)
+        ));
+        // Nonzero pivots, but solving with the factors overflows.
+        let near = diag(&[1.0, 1e-320]);
+        assert_eq!(near.condition_estimate_1norm().unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_solve() {
+        let a = Matrix::from_string("2 1 ; 1 3");
+        let x = a.solve(&Matrix::from_string("3 5 ; 4 10")).unwrap();
+
+        assert!(approx_eq(&x, &Matrix::from_string("1 1 ; 1 3")));
+        assert!(matches!(
+            Matrix::from_string("1 2 ; 2 4").solve(&Matrix::new(2, 1)),
+            Err(MatrixError::Singular)
+        ));
+    }
+
+    #[test]
+    fn test_solve_with_refinement() {
+        // Hilbert matrix, condition number ~1.5e7.
+        let n = 6;
+        let mut h = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                h[i][j] = 1.0 / (i + j + 1) as f64;
+            }
+        }
+        let x_true = Matrix {
+            rows: n,
+            cols: 1,
+            data: vec![1.0; n],
         };
+        let b = h.dot(&x_true);
 
-        assert!(m == expected);
+        let refined = h.solve_with_refinement(&b, 10).unwrap();
+        let plain = h.solve(&b).unwrap();
+        let error = |x: &Matrix| x.data.iter().map(|v| (v - 1.0).abs()).fold(0.0, f64::max);
+        assert!(error(&refined) <= error(&plain));
+        assert!(error(&refined) < 1e-8);
+        assert_eq!(h.solve_with_refinement(&b, 0).unwrap(), plain);
     }
 
     #[test]
-    fn test_display() {
+    fn test_from_raw_parts() {
+        let m = Matrix::from_raw_parts(2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(m, Matrix::from_string("1 2 ; 3 4"));
+        assert!(matches!(
+            Matrix::from_raw_parts(2, 3, vec![1.0; 5]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+
+        let unchecked = unsafe { Matrix::from_raw_parts_unchecked(1, 2, vec![5.0, 6.0]) };
+        assert_eq!(unchecked, Matrix::from_string("5 6"));
+    }
+
+    #[test]
+    fn test_to_raw_parts() {
         let m = Matrix::from_string("1 2 3 ; 4 5 6");
+        let ptr = m.data.as_ptr();
+        let (rows, cols, data) = m.to_raw_parts();
+
+        assert_eq!((rows, cols), (2, 3));
+        assert_eq!(data.as_ptr(), ptr);
+        assert_eq!(
+            Matrix::from_raw_parts(rows, cols, data).unwrap().data[5],
+            6.0
+        );
+    }
+
+    #[test]
+    fn test_clone_is_deep() {
+        let m = Matrix::from_string("1 2; 3 4");
+        let mut c = m.clone();
+        c[0][0] = 9.0;
+        c.apply_inplace(|x| x * 2.0);
+        assert_eq!(m.data, vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(c.data, vec![18.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn test_from_string_newlines() {
+        let m = Matrix::from_string(
+            "
+            1 2 3
+            4 5 6
+            ",
+        );
+        assert_eq!((m.rows, m.cols), (2, 3));
+        assert_eq!(m, Matrix::from_string("1 2 3; 4 5 6"));
+        // `;` takes precedence, so newlines inside a row are just whitespace.
+        assert_eq!(
+            Matrix::from_string("1 2\n3;4 5\n6"),
+            Matrix::from_string("1 2 3; 4 5 6")
+        );
+        assert_eq!(Matrix::from_string("1 2 3").rows, 1);
+    }
+
+    #[test]
+    fn test_from_file_comments() {
+        let path = std::env::temp_dir().join("linalg_test_from_file_comments.txt");
+        fs::write(
+            &path,
+            "# 2x3 matrix\n# source: experiment_1\n1 2 3\n\n  # midway note\n4 5 6\n",
+        )
+        .unwrap();
+        let m = Matrix::from_file(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(m, Matrix::from_string("1 2 3; 4 5 6"));
+    }
+
+    #[test]
+    fn test_minor() {
+        let m = Matrix::from_string("1 2 3; 4 5 6; 7 8 10");
+        assert_eq!(m.minor(1, 1), Matrix::from_string("1 3; 7 10"));
+        assert_eq!(m.minor(0, 2), Matrix::from_string("4 5; 7 8"));
+        assert!((m.cofactor(0, 1) - 2.0).abs() < 1e-12);
+        let one = Matrix::from_string("5");
+        assert_eq!((one.minor(0, 0).rows, one.minor(0, 0).cols), (0, 0));
+    }
+
+    #[test]
+    fn test_adjugate() {
+        let a = Matrix::from_string("2 0 1; 1 3 2; 1 1 2");
+        let adj = a.adjugate().unwrap();
+        assert!(approx_eq(
+            &adj,
+            &Matrix::from_string("4 1 -3; 0 3 -3; -2 -2 6")
+        ));
+        assert_eq!(a.det(), 6.0);
+        assert_eq!(a.dot(&adj), diag(&[6.0, 6.0, 6.0]));
+        assert_eq!(
+            Matrix::new(2, 3).adjugate(),
+            Err(MatrixError::NotSquare { rows: 2, cols: 3 })
+        );
+    }
+
+    #[test]
+    fn test_is_invertible() {
+        assert!(Matrix::from_string("2 1; 1 3").is_invertible());
+        assert!(!Matrix::from_string("1 2; 2 4").is_invertible());
+        assert!(!Matrix::new(2, 3).is_invertible());
+        // Scale does not matter for the default check.
+        assert!(Matrix::from_string("1e-9 0; 0 1e-9").is_invertible());
+        assert!(!Matrix::from_string("1 1; 1 1.0000000000000002").is_invertible());
+        // Products of row norms would overflow or underflow here.
+        assert!(Matrix::eye(3).apply(|x| 1e200 * x).is_invertible());
+        assert!(Matrix::eye(3).apply(|x| 1e-200 * x).is_invertible());
+        let near = Matrix::from_string("1 1; 1 1.0000000000000002");
+        assert!(!near.apply(|x| 1e200 * x).is_invertible());
+        assert!(!near.apply(|x| 1e-200 * x).is_invertible());
+        assert!(Matrix::new(0, 0).is_invertible());
+
+        let a = Matrix::from_string("1e-3 0; 0 1e-3");
+        assert!(a.is_invertible_with_tol(1e-7));
+        assert!(!a.is_invertible_with_tol(1e-5));
+        assert!(!Matrix::new(3, 2).is_invertible_with_tol(0.0));
+    }
+
+    #[test]
+    fn test_diag_prod_sum() {
+        let u = Matrix::from_string("2 5 7; 0 3 1; 0 0 4");
+        assert_eq!(u.diag_prod(), u.det());
+        assert_eq!(u.diag_sum(), u.trace());
+        let wide = Matrix::from_string("2 9 9; 9 3 9");
+        assert_eq!(wide.diag_prod(), 6.0);
+        assert_eq!(wide.transpose().diag_sum(), 5.0);
+        assert_eq!(Matrix::new(0, 0).diag_prod(), 1.0);
+    }
+
+    #[test]
+    fn test_rref_with_pivot_info() {
+        let a = Matrix::from_string("1 2 1 4; 2 4 0 6; 3 6 1 10");
+        let (r, pivots) = a.rref_with_pivot_info();
+        assert_eq!(pivots, vec![0, 2]);
+        assert!(approx_eq(
+            &r,
+            &Matrix::from_string("1 2 0 3; 0 0 1 1; 0 0 0 0")
+        ));
+        let (_, pivots) = Matrix::from_string("0 1; 1 0").rref_with_pivot_info();
+        assert_eq!(pivots, vec![0, 1]);
+        assert!(Matrix::new(2, 2).rref_with_pivot_info().1.is_empty());
+    }
+
+    #[test]
+    fn test_swap_rows() {
+        let mut m = Matrix::from_string("1 2; 3 4; 5 6");
+        m.swap_rows(0, 2);
+        assert_eq!(m, Matrix::from_string("5 6; 3 4; 1 2"));
+        m.swap_rows(1, 0);
+        assert_eq!(m, Matrix::from_string("3 4; 5 6; 1 2"));
+        m.swap_rows(2, 2);
+        assert_eq!(m, Matrix::from_string("3 4; 5 6; 1 2"));
+
+        // rref needs a row swap when the top-left entry is zero.
+        let mut a = Matrix::from_string("0 2 4; 1 1 1; 2 4 7");
+        a.rref();
+        assert!(approx_eq(&a, &Matrix::eye(3)));
+    }
+
+    #[test]
+    fn test_rref_pivoting() {
+        let mut a = Matrix::from_string("0 1; 1 0");
+        a.rref();
+        assert!(approx_eq(&a, &Matrix::eye(2)));
+
+        // The zero pivot only shows up after eliminating the first column.
+        let mut b = Matrix::from_string("1 1 1; 1 1 2; 0 1 1");
+        b.rref();
+        assert!(approx_eq(&b, &Matrix::eye(3)));
+
+        let mut c = Matrix::from_string("1 2; 2 4; 3 6");
+        c.rref();
+        assert!(approx_eq(&c, &Matrix::from_string("1 2; 0 0; 0 0")));
+    }
+
+    #[test]
+    fn test_det_large() {
+        // Lower triangular times upper triangular with known diagonals.
+        let n = 20;
+        let mut l = Matrix::eye(n);
+        let mut u = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                let v = ((i * 7 + j * 3) % 5) as f64 - 2.0;
+                if j < i {
+                    l[i][j] = v;
+                } else if j > i {
+                    u[i][j] = v;
+                }
+            }
+            u[i][i] = if i % 2 == 0 { 2.0 } else { -0.5 };
+        }
+        let a = l.dot(&u);
+        // Ten factors of 2 and ten of -0.5.
+        assert!((a.det() - 1.0).abs() < 1e-8);
+
+        let mut p = Matrix::eye(n);
+        p.swap_rows(0, n - 1);
+        assert_eq!(p.det(), -1.0);
+        assert_eq!(Matrix::new(n, n).det(), 0.0);
+    }
+
+    #[test]
+    fn test_inverse_large() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let n = 20;
+        let mut a = Matrix::new(n, n);
+        a.data
+            .iter_mut()
+            .for_each(|x| *x = rng.gen_range(-1.0..1.0));
+        let inv = a.inverse();
+        assert!(approx_eq(&a.dot(&inv), &Matrix::eye(n)));
+        assert!(approx_eq(&inv.dot(&a), &Matrix::eye(n)));
+    }
+
+    #[test]
+    fn test_round_near_integers() {
+        let mut m = Matrix::from_string("0.9999999999 -0.0 1e-12; 2.5 -3.0000001 -1e-13");
+        m.round_near_integers(1e-9);
+        assert_eq!(m, Matrix::from_string("1 0 0; 2.5 -3.0000001 0"));
+        m.round_near_integers(1e-6);
+        assert_eq!(m, Matrix::from_string("1 0 0; 2.5 -3 0"));
+    }
+
+    #[test]
+    fn test_empty_matrix() {
+        let e = Matrix::new(0, 0);
+        e.print();
+        e.print_labeled(&[], &[]);
+        assert_eq!(e.to_string(), "[]\n");
+        assert_eq!(Matrix::new(2, 0).to_string(), "[]\n[]\n");
+        assert_eq!(Matrix::from_json(&e.to_json()).unwrap(), e);
+
+        let mut m = e.clone();
+        m.identity();
+        m.apply_inplace(|x| x + 1.0);
+        m.rref();
+        m.round_near_integers(1e-9);
+        assert_eq!(m, e);
+        assert_eq!(e.zip_apply(&e, |x, y| x + y).unwrap(), e);
+        assert_eq!(e.dot(&e), e);
+        assert_eq!(e.transpose(), e);
+        assert_eq!(e.inverse(), e);
+        assert_eq!(e.adjugate().unwrap(), e);
+        assert_eq!(e.with_diagonal(&[]).unwrap(), e);
+        assert_eq!(e.det(), 1.0);
+        assert_eq!(e.trace(), 0.0);
+        assert_eq!((e.diag_prod(), e.diag_sum()), (1.0, 0.0));
+        assert!(e.is_invertible());
+        assert_eq!(e.rref_with_pivot_info(), (e.clone(), vec![]));
+        assert_eq!(e.map_rows(|r| r.to_vec()).unwrap(), e);
+        assert_eq!(e.map_cols(|c| c.to_vec()).unwrap(), e);
+        assert!(e.fold_rows(0.0, |a, x| a + x).is_empty());
+        assert!(e.fold_cols(0.0, |a, x| a + x).is_empty());
+        assert_eq!(e.position(|_| true), None);
+        assert_eq!(e.find(|_| true), None);
+        assert_eq!(e.bandwidth(0.0), (0, 0));
+        assert!(e.is_banded(0, 0, 0.0));
+        assert_eq!(e.projection_onto_columns(), e);
+        assert_eq!(e.solve(&e).unwrap(), e);
+        assert_eq!(e.spectral_radius().unwrap(), 0.0);
+        assert!(e.is_convergent().unwrap());
+        assert!(e.drazin_inverse().is_ok());
+        assert!(e.ilu0().is_ok());
+        assert!(e.svd().is_ok());
+        assert!(e.truncated_svd(0).is_ok());
+        assert!(e.to_hessenberg().is_ok());
+        assert!(e.polar().is_ok());
+        assert!(e.condition_estimate_1norm().is_ok());
+        assert!(e.to_tridiagonal(0.0).is_some());
+    }
+
+    #[test]
+    fn test_dot() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        let b = Matrix::from_string("1 0 0 1; 0 1 0 1; 0 0 1 1");
+        assert_eq!(a.dot(&b), Matrix::from_string("1 2 3 6; 4 5 6 15"));
+        let row = Matrix::from_string("1 2 3");
+        let col = Matrix::from_string("1; 1; 1");
+        assert_eq!(row.dot(&col), Matrix::from_string("6"));
+        assert_eq!(col.dot(&row).rows, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "M1 is 2 by 3, M2 is 2 by 3")]
+    fn test_dot_mismatch() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        a.dot(&a);
+    }
+
+    #[test]
+    fn test_from_string_edge_semicolons() {
+        let expected = Matrix::from_string("1 2; 3 4");
+        assert_eq!(Matrix::from_string("1 2; 3 4;"), expected);
+        assert_eq!(Matrix::from_string("; 1 2; 3 4"), expected);
+        assert_eq!(Matrix::from_string(" ;; 1 2; 3 4 ; ; "), expected);
+        assert_eq!(Matrix::from_string(";"), Matrix::new(0, 0));
+        // An empty row in the middle is still an error.
+        assert!(matches!(
+            "1 2;; 3 4".parse::<Matrix>(),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_norm() {
+        let m = Matrix::from_string("1 -2; 2 4");
+        assert_eq!(m.norm_sq(), 25.0);
+        assert_eq!(m.norm(), 5.0);
+        assert_eq!(Matrix::new(0, 0).norm(), 0.0);
+    }
+
+    #[test]
+    fn test_cumsum() {
+        let m = Matrix::from_string("1 2 3; 4 5 6");
+        assert_eq!(m.cumsum_row(), Matrix::from_string("1 3 6; 4 9 15"));
+        assert_eq!(m.cumsum_col(), Matrix::from_string("1 2 3; 5 7 9"));
+        assert_eq!(Matrix::new(0, 3).cumsum_col(), Matrix::new(0, 3));
+    }
+
+    #[test]
+    fn test_conv1d_rows() {
+        let m = Matrix::from_string("1 2 3 4; 0 1 0 0");
+        let valid = m.conv1d_rows(&[1.0, 0.0, -1.0]).unwrap();
+        assert_eq!(valid, Matrix::from_string("2 2; 0 -1"));
+        let same = m.conv1d_rows_same(&[1.0, 0.0, -1.0]).unwrap();
+        assert_eq!(same, Matrix::from_string("2 2 2 -3; 1 0 -1 0"));
+        // The kernel is flipped, unlike a correlation.
+        assert_eq!(
+            m.conv1d_rows(&[1.0, 2.0]).unwrap(),
+            Matrix::from_string("4 7 10; 1 2 0")
+        );
+        assert!(matches!(
+            m.conv1d_rows(&[1.0; 5]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            m.conv1d_rows_same(&[]),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_difference_matrix() {
+        let d1 = Matrix::difference_matrix(4, 1);
+        assert_eq!(d1, Matrix::from_string("-1 1 0 0; 0 -1 1 0; 0 0 -1 1"));
+        let d2 = Matrix::difference_matrix(4, 2);
+        assert_eq!(d2, Matrix::from_string("1 -2 1 0; 0 1 -2 1"));
+        let x = Matrix::from_string("1; 4; 9; 16");
+        assert_eq!(d2.dot(&x), Matrix::from_string("2; 2"));
+        assert_eq!(Matrix::difference_matrix(3, 0), Matrix::eye(3));
+        assert_eq!(Matrix::difference_matrix(3, 5), Matrix::new(0, 3));
+    }
+
+    #[test]
+    fn test_to_vec_of_rows_cols() {
+        let m = Matrix::from_string("1 2 3; 4 5 6");
+        let rows = m.to_vec_of_rows();
+        assert_eq!(rows, vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        assert_eq!(
+            m.to_vec_of_cols(),
+            vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]
+        );
+        let data = rows.into_iter().flatten().collect();
+        assert_eq!(Matrix::from_raw_parts(2, 3, data).unwrap(), m);
+        assert!(Matrix::new(0, 2).to_vec_of_rows().is_empty());
+        assert_eq!(Matrix::new(0, 2).to_vec_of_cols(), vec![vec![], vec![]]);
+    }
+
+    #[test]
+    fn test_scale_row_col() {
+        let mut m = Matrix::from_string("1 2; 3 4");
+        m.scale_row(1, 2.0).unwrap();
+        assert_eq!(m, Matrix::from_string("1 2; 6 8"));
+        m.scale_col(0, -1.0).unwrap();
+        assert_eq!(m, Matrix::from_string("-1 2; -6 8"));
+        assert_eq!(
+            m.scale_row(2, 1.0),
+            Err(MatrixError::IndexOutOfBounds { index: 2, len: 2 })
+        );
+        assert!(m.scale_col(5, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_add_scaled_row() {
+        let mut m = Matrix::from_string("1 2; 3 4");
+        m.add_scaled_row(1, 0, -3.0).unwrap();
+        assert_eq!(m, Matrix::from_string("1 2; 0 -2"));
+        m.add_scaled_row(0, 0, 1.0).unwrap();
+        assert_eq!(m, Matrix::from_string("2 4; 0 -2"));
+        assert_eq!(
+            m.add_scaled_row(0, 3, 1.0),
+            Err(MatrixError::IndexOutOfBounds { index: 3, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_augment() {
+        let a = Matrix::from_string("1 2; 3 4");
+        let b = Matrix::from_string("5; 6");
+        assert_eq!(a.augment(&b).unwrap(), Matrix::from_string("1 2 5; 3 4 6"));
+        assert_eq!(a.augment(&Matrix::new(2, 0)).unwrap(), a);
+        assert!(matches!(
+            a.augment(&Matrix::new(3, 1)),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_solution() {
+        let a = Matrix::from_string("2 1 -1; -3 -1 2; -2 1 2");
+        let b = Matrix::from_string("8; -11; -3");
+        let mut aug = a.augment(&b).unwrap();
+        aug.rref();
+        let x = aug.extract_solution().unwrap();
+        assert!(x
+            .iter()
+            .zip([2.0, 3.0, -1.0])
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+
+        // Extra consistent equations leave zero rows behind.
+        let mut over = Matrix::from_string("1 0 1; 0 1 2; 1 1 3");
+        over.rref();
+        assert_eq!(over.extract_solution().unwrap(), vec![1.0, 2.0]);
+
+        let mut inconsistent = Matrix::from_string("1 1 1; 1 1 2");
+        inconsistent.rref();
+        assert_eq!(
+            inconsistent.extract_solution(),
+            Err(MatrixError::Inconsistent)
+        );
+        let mut free = Matrix::from_string("1 1 2; 2 2 4");
+        free.rref();
+        assert_eq!(free.extract_solution(), Err(MatrixError::Singular));
+        assert!(matches!(
+            Matrix::from_string("2 0 4; 0 1 1").extract_solution(),
+            Err(MatrixError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_trace_power() {
+        // Closed walks in a triangle: tr(A^k) = 2^k + 2 (-1)^k.
+        let a = Matrix::from_string("0 1 1; 1 0 1; 1 1 0");
+        for k in 0..8 {
+            let expected = 2f64.powi(k as i32) + 2.0 * (-1f64).powi(k as i32);
+            assert_eq!(a.trace_power(k).unwrap(), expected);
+        }
+        let b = Matrix::from_string("1 2; 3 4");
+        assert_eq!(b.trace_power(3).unwrap(), b.dot(&b).dot(&b).trace());
+        assert!(Matrix::new(2, 3).trace_power(2).is_err());
+    }
+
+    #[test]
+    fn test_diagonal_matrix_multiply() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        assert_eq!(
+            a.diagonal_matrix_multiply_left(&[2.0, -1.0]).unwrap(),
+            diag(&[2.0, -1.0]).dot(&a)
+        );
+        assert_eq!(
+            a.diagonal_matrix_multiply_right(&[1.0, 0.5, 2.0]).unwrap(),
+            a.dot(&diag(&[1.0, 0.5, 2.0]))
+        );
+        assert!(a.diagonal_matrix_multiply_left(&[1.0; 3]).is_err());
+        assert!(a.diagonal_matrix_multiply_right(&[1.0; 2]).is_err());
+    }
+
+    #[test]
+    fn test_shape() {
+        let m = Matrix::new(3, 4);
+        m.print_shape();
+        assert_eq!(m.shape(), (3, 4));
+        assert!(!m.is_square() && !m.is_empty());
+        assert!(Matrix::new(2, 2).is_square());
+        assert!(Matrix::new(0, 3).is_empty() && Matrix::new(3, 0).is_empty());
+        assert!(Matrix::default().is_square() && Matrix::default().is_empty());
+    }
+
+    static_assertions::assert_impl_all!(Matrix: Send, Sync);
+    static_assertions::assert_impl_all!(TridiagonalMatrix: Send, Sync);
+    static_assertions::assert_impl_all!(MatrixError: Send, Sync);
+
+    #[test]
+    fn test_from_buffer() {
+        let buf = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let m = Matrix::from_buffer(2, 3, &buf).unwrap();
+        assert_eq!(m, Matrix::from_string("1 2 3; 4 5 6"));
+        assert!(Matrix::from_buffer(4, 2, &buf).is_err());
+        let p = unsafe { Matrix::from_raw_ptr(3, 2, buf.as_ptr(), buf.len()) };
+        assert_eq!(p, Matrix::from_string("1 2; 3 4; 5 6"));
+    }
+
+    #[test]
+    fn test_write_to_file_and_string_repr() {
+        let m = Matrix::from_string("1 -2.5 0.1; 1e-300 3 4");
+        let third = m.apply(|x| x / 3.0);
+
+        let path = std::env::temp_dir().join("linalg_test_write_to_file.txt");
+        third.write_to_file(path.to_str().unwrap()).unwrap();
+        let back = Matrix::from_file(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(back, third);
+
+        assert_eq!(m.to_string_repr(), "1.0 -2.5 0.1; 1e-300 3.0 4.0");
+        assert_eq!(Matrix::from_string(&third.to_string_repr()), third);
+        let col = Matrix::from_string("1; 2");
+        assert_eq!(Matrix::from_string(&col.to_string_repr()), col);
+
+        let missing = std::env::temp_dir().join("linalg_no_such_dir/m.txt");
+        assert!(matches!(
+            m.write_to_file(missing.to_str().unwrap()),
+            Err(MatrixError::Io { .. })
+        ));
+    }
 
-        assert_eq!("[1.0, 2.0, 3.0]\n[4.0, 5.0, 6.0]\n", m.to_string())
+    #[test]
+    fn test_apply() {
+        let m = Matrix::from_string("1 2; 3 4");
+        let doubled = m.apply(|x| x * 2.0);
+        assert_eq!(m, Matrix::from_string("1 2; 3 4"));
+        assert_eq!(doubled, Matrix::from_string("2 4; 6 8"));
+        let mut n = m.clone();
+        n.apply_inplace(|x| x * 2.0);
+        assert_eq!(n, doubled);
+    }
+
+    #[test]
+    fn test_scan_rows() {
+        let m = Matrix::from_string("3 1 4 1; 5 9 2 6");
+        assert_eq!(
+            m.scan_rows(f64::NEG_INFINITY, f64::max),
+            Matrix::from_string("3 3 4 4; 5 9 9 9")
+        );
+        assert_eq!(m.scan_rows(1.0, |acc, x| acc * x).data[3], 12.0);
+        assert_eq!(m.scan_rows(0.0, |acc, x| acc + x), m.cumsum_row());
+    }
+
+    #[test]
+    fn test_col_pivot_qr() {
+        // Rank 2: the third column is the sum of the first two.
+        let a = Matrix::from_string("1 2 3; 4 5 9; 7 8 15; 1 0 1");
+        let (q, r, perm) = a.col_pivot_qr().unwrap();
+        assert_eq!((q.rows, q.cols, r.rows, r.cols), (4, 4, 4, 3));
+        assert_eq!(perm[0], 2);
+        assert!(approx_eq(&q.transpose().dot(&q), &Matrix::eye(4)));
+
+        let mut ap = Matrix::new(4, 3);
+        for i in 0..4 {
+            for (j, p) in perm.iter().enumerate() {
+                ap[i][j] = a[i][*p];
+            }
+        }
+        assert!(approx_eq(&q.dot(&r), &ap));
+        for i in 0..4 {
+            assert!(r[i][..i.min(3)].iter().all(|x| *x == 0.0));
+        }
+        assert!(r[0][0].abs() >= r[1][1].abs());
+        assert!(r[1][1].abs() > 1e-8 && r[2][2].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_nmf() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let w0 = Matrix::from_string("1 0; 2 1; 0 3; 1 1");
+        let h0 = Matrix::from_string("1 2 0 1 3; 0 1 2 1 0");
+        let a = w0.dot(&h0);
+        let mut rng = StdRng::seed_from_u64(3);
+        let (w, h) = a.nmf_with_rng(2, 5000, 1e-12, &mut rng).unwrap();
+        assert_eq!((w.rows, w.cols, h.rows, h.cols), (4, 2, 2, 5));
+        assert!(w.data.iter().chain(&h.data).all(|x| *x >= 0.0));
+        let residual = a.zip_apply(&w.dot(&h), |x, y| x - y).unwrap();
+        assert!(residual.norm() < 1e-2 * a.norm());
+
+        assert!(a.nmf(2, 10, 1e-6).is_ok());
+        assert!(matches!(
+            Matrix::from_string("1 -1; 0 1").nmf(1, 10, 1e-6),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(a.nmf(0, 10, 1e-6).is_err());
+    }
+
+    #[test]
+    fn test_softmax() {
+        let m = Matrix::from_string("1 2 3; 1000 1000 1000");
+        let rows = m.softmax(1).unwrap();
+        let e: f64 = 1.0 + 1f64.exp() + 2f64.exp();
+        assert!((rows[0][0] - 1.0 / e).abs() < 1e-15);
+        assert!((rows[0][2] - 2f64.exp() / e).abs() < 1e-15);
+        assert!(rows[1].iter().all(|x| (x - 1.0 / 3.0).abs() < 1e-15));
+
+        let cols = m.softmax(0).unwrap();
+        for c in 0..3 {
+            assert!((cols[0][c] + cols[1][c] - 1.0).abs() < 1e-15);
+        }
+        assert_eq!(cols[1][0], 1.0);
+        assert!(m.softmax(2).is_err());
+    }
+
+    #[test]
+    fn test_log_softmax() {
+        let m = Matrix::from_string("1 2 3; -1000 0 1000");
+        let logs = m.log_softmax(1).unwrap();
+        let soft = m.softmax(1).unwrap();
+        for c in 0..3 {
+            assert!((logs[0][c] - soft[0][c].ln()).abs() < 1e-14);
+        }
+        // softmax underflows to 0 here, but the log stays finite.
+        assert_eq!(logs[1][0], -2000.0);
+        assert_eq!(logs[1][2], 0.0);
+        let cols = m.log_softmax(0).unwrap();
+        assert!(cols[0][0].abs() < 1e-12);
+        assert_eq!(cols[1][0], -1001.0);
+        assert!(m.log_softmax(3).is_err());
+    }
+
+    #[test]
+    fn test_activations() {
+        let m = Matrix::from_string("-2 0 3");
+        assert_eq!(m.relu(), Matrix::from_string("0 0 3"));
+        assert_eq!(m.leaky_relu(0.1), Matrix::from_string("-0.2 0 3"));
+        let s = Matrix::from_string("0 -1000 1000").sigmoid();
+        assert_eq!(s.data, vec![0.5, 0.0, 1.0]);
+        let t = m.sigmoid();
+        assert!((t[0][0] - 1.0 / (1.0 + 2f64.exp())).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_hadamard() {
+        let a = Matrix::from_string("1 2; 3 4");
+        let b = Matrix::from_string("2 0; -1 0.5");
+        assert_eq!(a.hadamard(&b).unwrap(), Matrix::from_string("2 0; -3 2"));
+        assert!(matches!(
+            a.hadamard(&Matrix::new(2, 3)),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_frobenius_inner_product() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        let b = Matrix::from_string("1 0 -1; 2 1 0");
+        let ip = a.frobenius_inner_product(&b).unwrap();
+        assert_eq!(ip, 11.0);
+        assert_eq!(ip, a.transpose().dot(&b).trace());
+        assert_eq!(a.frobenius_inner_product(&a).unwrap(), a.norm_sq());
+        assert!(matches!(
+            a.frobenius_inner_product(&a.transpose()),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_strided_iter() {
+        let m = Matrix::from_string("0 1 2 3 4; 5 6 7 8 9; 10 11 12 13 14");
+        let v: Vec<f64> = m.strided_iter(2, 2).unwrap().collect();
+        assert_eq!(v, vec![0.0, 2.0, 4.0, 10.0, 12.0, 14.0]);
+        let v: Vec<f64> = m.strided_iter(1, 3).unwrap().collect();
+        assert_eq!(v, vec![0.0, 3.0, 5.0, 8.0, 10.0, 13.0]);
+        assert_eq!(
+            m.strided_iter(10, 10).unwrap().collect::<Vec<_>>(),
+            vec![0.0]
+        );
+        assert!(m.strided_iter(0, 1).is_err());
+    }
+
+    #[test]
+    fn test_repeat_vector() {
+        let data = Matrix::from_string("1 2; 3 6; 5 10");
+        let mean = Matrix::repeat_vector(&[3.0, 6.0], 3);
+        assert_eq!(mean, Matrix::from_string("3 6; 3 6; 3 6"));
+        let centered = data.zip_apply(&mean, |x, m| x - m).unwrap();
+        assert_eq!(centered, Matrix::from_string("-2 -4; 0 0; 2 4"));
+        assert_eq!(
+            Matrix::repeat_col_vector(&[1.0, 2.0], 3),
+            Matrix::from_string("1 1 1; 2 2 2")
+        );
+        assert_eq!(Matrix::repeat_vector(&[1.0], 0).shape(), (0, 1));
+    }
+
+    #[test]
+    fn test_nan_checks() {
+        let mut m = Matrix::from_string("1 NaN; inf 2");
+        assert!(!m.is_nan_free() && !m.is_finite());
+        m.replace_nan(0.0);
+        assert!(m.is_nan_free() && !m.is_finite());
+        assert_eq!(m, Matrix::from_string("1 0; inf 2"));
+        assert!(Matrix::from_string("1 2").is_finite());
+    }
+
+    #[test]
+    fn test_mask_apply_where() {
+        let m = Matrix::from_string("1 -2; -3 4");
+        let masked = m.mask_apply(&[false, true, true, false], 0.0).unwrap();
+        assert_eq!(masked, Matrix::from_string("1 0; 0 4"));
+        assert!(m.mask_apply(&[true], 0.0).is_err());
+        assert_eq!(
+            m.where_(|x| x > 0.0, 1.0, -1.0),
+            Matrix::from_string("1 -1; -1 1")
+        );
+    }
+
+    #[test]
+    fn test_outer_sum() {
+        let m = Matrix::outer_sum(&[1.0, 10.0], &[0.0, 1.0, 2.0]);
+        assert_eq!(m, Matrix::from_string("1 2 3; 10 11 12"));
+        assert_eq!(Matrix::outer_sum(&[], &[1.0]).shape(), (0, 1));
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        let x = Matrix::from_string("0 0; 3 4");
+        let y = Matrix::from_string("0 0; 6 8; 3 4");
+        let d = Matrix::distance_matrix(&x, &y).unwrap();
+        assert!(approx_eq(&d, &Matrix::from_string("0 10 5; 5 5 0")));
+        assert!(matches!(
+            Matrix::distance_matrix(&x, &Matrix::new(1, 3)),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_rbf_kernel() {
+        let x = Matrix::from_string("0 0; 1 0; 0 2");
+        let k = Matrix::rbf_kernel(&x, 0.5);
+        assert_eq!(k, k.transpose());
+        assert!((0..3).all(|i| k[i][i] == 1.0));
+        assert!((k[0][1] - (-0.5f64).exp()).abs() < 1e-15);
+        assert!((k[1][2] - (-2.5f64).exp()).abs() < 1e-15);
+        let (values, _) = k.symmetric_eigen().unwrap();
+        assert!(values.iter().all(|v| *v > -1e-12));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_apply_parallel() {
+        let a = Matrix::from_string("1 -2 3; 0.5 4 -6");
+        let mut b = a.clone();
+        b.apply_parallel(f64::exp);
+        assert_eq!(b, a.apply(f64::exp));
+    }
+
+    #[test]
+    fn test_dgemm() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        let b = Matrix::from_string("1 0; -1 2; 0.5 1");
+        let c = Matrix::from_string("1 1; 2 -3");
+        let expected = a
+            .dot(&b)
+            .apply(|x| 2.0 * x)
+            .zip_apply(&c, |p, q| p - 0.5 * q)
+            .unwrap();
+        assert!(approx_eq(&a.dgemm(&b, &c, 2.0, -0.5).unwrap(), &expected));
+
+        let nan = Matrix::from_string("NaN NaN; NaN NaN");
+        assert!(approx_eq(&a.dgemm(&b, &nan, 1.0, 0.0).unwrap(), &a.dot(&b)));
+
+        assert!(matches!(
+            a.dgemm(&a, &c, 1.0, 1.0),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            a.dgemm(&b, &a, 1.0, 1.0),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_trace_rectangular() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        assert_eq!(a.trace(), 6.0);
+        assert_eq!(a.transpose().trace(), 6.0);
+        assert_eq!(Matrix::new(0, 3).trace(), 0.0);
+    }
+
+    #[test]
+    fn test_abs_extremes() {
+        let a = Matrix::from_string("0 -3 0.5; 2 0 -0.25");
+        assert_eq!(a.max_abs_element(), 3.0);
+        assert_eq!(a.min_abs_nonzero(), Some(0.25));
+        assert_eq!(Matrix::new(2, 2).max_abs_element(), 0.0);
+        assert_eq!(Matrix::new(2, 2).min_abs_nonzero(), None);
+        assert_eq!(Matrix::default().min_abs_nonzero(), None);
+    }
+
+    #[test]
+    fn test_polyval() {
+        let a = Matrix::from_string("1 2; 3 4");
+        // 2A^2 - A + 3I
+        let expected = a
+            .dot(&a)
+            .apply(|x| 2.0 * x)
+            .zip_apply(&a, |p, q| p - q)
+            .unwrap()
+            .zip_apply(&Matrix::eye(2), |p, q| p + 3.0 * q)
+            .unwrap();
+        assert_eq!(a.polyval(&[2.0, -1.0, 3.0]).unwrap(), expected);
+        assert_eq!(a.polyval(&[5.0]).unwrap(), diag(&[5.0, 5.0]));
+        assert_eq!(a.polyval(&[]).unwrap(), Matrix::new(2, 2));
+
+        // Cayley-Hamilton: A satisfies its characteristic polynomial.
+        assert_eq!(a.polyval(&[1.0, -5.0, -2.0]).unwrap(), Matrix::new(2, 2));
+
+        assert!(matches!(
+            Matrix::new(2, 3).polyval(&[1.0]),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_exp_matrix() {
+        assert_eq!(Matrix::new(3, 3).exp_matrix().unwrap(), Matrix::eye(3));
+        let d = diag(&[1.0, -2.0, 10.0]).exp_matrix().unwrap();
+        assert!(close_rel(
+            1e-12,
+            &d,
+            &diag(&[1f64.exp(), (-2f64).exp(), 10f64.exp()])
+        ));
+
+        // Nilpotent: e^N = I + N.
+        let n = Matrix::from_string("0 1 2; 0 0 3; 0 0 0");
+        let expected = Matrix::from_string("1 1 3.5; 0 1 3; 0 0 1");
+        assert!(close_rel(1e-12, &n.exp_matrix().unwrap(), &expected));
+
+        // Skew-symmetric generators give rotations.
+        let t = 2.5f64;
+        let r = Matrix::from_string(&format!("0 {}; {} 0", -t, t));
+        let expected = Matrix::from_string(&format!(
+            "{} {}; {} {}",
+            t.cos(),
+            -t.sin(),
+            t.sin(),
+            t.cos()
+        ));
+        assert!(close_rel(1e-12, &r.exp_matrix().unwrap(), &expected));
+
+        assert!(matches!(
+            Matrix::new(2, 3).exp_matrix(),
+            Err(MatrixError::NotSquare { .. })
+        ));
+        assert!(diag(&[f64::NAN, 1.0]).exp_matrix().is_err());
+    }
+
+    #[test]
+    fn test_log_matrix() {
+        let e = 1f64.exp();
+        assert!(close_rel(
+            1e-10,
+            &diag(&[e, e * e, 1.0]).log_matrix().unwrap(),
+            &diag(&[1.0, 2.0, 0.0])
+        ));
+        assert!(approx_eq(
+            &Matrix::eye(3).log_matrix().unwrap(),
+            &Matrix::new(3, 3)
+        ));
+
+        // Jordan block: log [[a, 1], [0, a]] = [[ln a, 1/a], [0, ln a]].
+        let j = Matrix::from_string("2 1; 0 2");
+        let expected = Matrix::from_string(&format!("{0} 0.5; 0 {0}", 2f64.ln()));
+        assert!(close_rel(1e-10, &j.log_matrix().unwrap(), &expected));
+
+        // Rotations: the log recovers the skew-symmetric generator.
+        let g = Matrix::from_string("0 -0.5 0.2; 0.5 0 -1.1; -0.2 1.1 0");
+        assert!(close_rel(
+            1e-10,
+            &g.exp_matrix().unwrap().log_matrix().unwrap(),
+            &g
+        ));
+
+        let a =
+            Matrix::from_string("0.3 1 -0.2 0.1; 0.5 -0.4 0.3 0; 0 0.2 0.1 -0.7; 0.4 0 0.6 0.2");
+        assert!(close_rel(
+            1e-10,
+            &a.exp_matrix().unwrap().log_matrix().unwrap(),
+            &a
+        ));
+        let b = Matrix::from_string("4 1 0; 1 3 1; 0 1 2");
+        assert!(close_rel(
+            1e-10,
+            &b.log_matrix().unwrap().exp_matrix().unwrap(),
+            &b
+        ));
+
+        assert!(matches!(
+            diag(&[-1.0, 2.0]).log_matrix(),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(Matrix::from_string("1 2; 2 1").log_matrix().is_err());
+        assert!(Matrix::new(2, 2).log_matrix().is_err());
+        assert!(matches!(
+            Matrix::new(2, 3).log_matrix(),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sqrt_matrix() {
+        let check = |a: &Matrix| {
+            let s = a.sqrt_matrix().unwrap();
+            let s2 = s.dot(&s);
+            let scale = a.max_abs_element().max(1.0);
+            assert!(s2
+                .data
+                .iter()
+                .zip(&a.data)
+                .all(|(x, y)| (x - y).abs() <= 1e-12 * scale));
+            s
+        };
+
+        assert!(approx_eq(
+            &check(&diag(&[4.0, 9.0, 0.0])),
+            &diag(&[2.0, 3.0, 0.0])
+        ));
+        // The root of an SPD matrix is its SPD root.
+        let s = check(&Matrix::from_string("5 4; 4 5"));
+        assert!(approx_eq(&s, &Matrix::from_string("2 1; 1 2")));
+        // A rotation by 2t has the rotation by t as its principal root.
+        let s = check(&Matrix::from_string("0 -1; 1 0"));
+        let h = 0.5f64.sqrt();
+        assert!(approx_eq(
+            &s,
+            &Matrix::from_string(&format!("{h} {}; {h} {h}", -h))
+        ));
+        check(&Matrix::from_string(
+            "1 2 0 -1; 0.5 3 1 0; -1 0 2 1; 0 1 -0.5 4",
+        ));
+        check(&Matrix::from_string("2 1; 0 2"));
+
+        assert!(matches!(
+            diag(&[-4.0, 1.0]).sqrt_matrix(),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        // Nilpotent: no square root exists.
+        assert!(Matrix::from_string("0 1; 0 0").sqrt_matrix().is_err());
+        assert!(matches!(
+            Matrix::new(2, 3).sqrt_matrix(),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bilinear_form() {
+        let a = Matrix::from_string("1 2 3; 4 5 6");
+        assert_eq!(
+            Matrix::bilinear_form(&[1.0, -1.0], &a, &[1.0, 0.0, 2.0]).unwrap(),
+            -9.0
+        );
+        assert_eq!(
+            Matrix::bilinear_form(&[0.0, 0.0], &a, &[1.0, 1.0, 1.0]).unwrap(),
+            0.0
+        );
+        assert!(matches!(
+            Matrix::bilinear_form(&[1.0, 1.0, 1.0], &a, &[1.0, 1.0, 1.0]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(Matrix::bilinear_form(&[1.0, 1.0], &a, &[1.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_rayleigh_quotient() {
+        let a = Matrix::from_string("2 1; 1 2");
+        assert_eq!(a.rayleigh_quotient(&[1.0, 1.0]).unwrap(), 3.0);
+        assert_eq!(a.rayleigh_quotient(&[-2.0, 2.0]).unwrap(), 1.0);
+        let r = a.rayleigh_quotient(&[1.0, 0.3]).unwrap();
+        assert!((1.0..=3.0).contains(&r));
+
+        assert!(matches!(
+            a.rayleigh_quotient(&[0.0, 0.0]),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            a.rayleigh_quotient(&[1.0]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            Matrix::new(2, 3).rayleigh_quotient(&[1.0, 1.0]),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sylvester_solve() {
+        let check = |a: &Matrix, b: &Matrix, x: &Matrix| {
+            let c = a.dot(x).zip_apply(&x.dot(b), |p, q| p + q).unwrap();
+            let y = Matrix::sylvester_solve(a, b, &c).unwrap();
+            assert!(y
+                .data
+                .iter()
+                .zip(&x.data)
+                .all(|(p, q)| (p - q).abs() < 1e-10));
+        };
+
+        let a = Matrix::from_string("1 2 0; -2 1 1; 0 0.5 3");
+        let b = Matrix::from_string("4 -1; 2 5");
+        check(&a, &b, &Matrix::from_string("1 0; -1 2; 0.5 3"));
+        // Complex eigenvalue pairs on both sides.
+        let b = Matrix::from_string("0 1 0 0; -4 0 0 1; 0 0 2 -3; 1 0 3 2");
+        check(&a, &b, &Matrix::from_string("1 2 3 4; 0 -1 0 1; 2 2 -2 0"));
+        check(&diag(&[1.0]), &diag(&[2.0]), &diag(&[3.0]));
+
+        // A and -B share the eigenvalue 1.
+        assert!(matches!(
+            Matrix::sylvester_solve(&diag(&[1.0, 2.0]), &diag(&[-1.0]), &Matrix::new(2, 1)),
+            Err(MatrixError::Singular)
+        ));
+        assert!(matches!(
+            Matrix::sylvester_solve(&Matrix::eye(2), &Matrix::eye(3), &Matrix::new(3, 2)),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            Matrix::sylvester_solve(&Matrix::new(2, 3), &Matrix::eye(3), &Matrix::new(2, 3)),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lyapunov_solve() {
+        let residual = |a: &Matrix, q: &Matrix, x: &Matrix| {
+            let r = a.dot(x).dot(&a.transpose());
+            r.data
+                .iter()
+                .zip(&x.data)
+                .zip(&q.data)
+                .map(|((r, x), q)| (r - x + q).abs())
+                .fold(0.0, f64::max)
+        };
+
+        let a = Matrix::from_string("0.5 0.2 0; -0.1 0.3 0.4; 0 0.2 -0.6");
+        let q = Matrix::from_string("2 1 0; 1 2 0; 0 0 1");
+        let x = Matrix::lyapunov_solve(&a, &q).unwrap();
+        assert!(residual(&a, &q, &x) < 1e-12);
+        assert_eq!(x, x.transpose());
+
+        // Stable A: X is the series sum_k A^k Q (A^T)^k.
+        let mut series = q.clone();
+        let mut term = q.clone();
+        for _ in 0..200 {
+            term = a.dot(&term).dot(&a.transpose());
+            series = series.zip_apply(&term, |s, t| s + t).unwrap();
+        }
+        assert!(approx_eq(&x, &series));
+
+        // Scalar case: x = q / (1 - a^2), and a rotation-like complex pair.
+        let x = Matrix::lyapunov_solve(&diag(&[0.5]), &diag(&[3.0])).unwrap();
+        assert!((x[0][0] - 4.0).abs() < 1e-12);
+        let r = Matrix::from_string("0.6 -0.7; 0.7 0.6");
+        let x = Matrix::lyapunov_solve(&r, &Matrix::eye(2)).unwrap();
+        assert!(residual(&r, &Matrix::eye(2), &x) < 1e-12);
+
+        // Eigenvalues 2 and 0.5 multiply to 1.
+        assert!(matches!(
+            Matrix::lyapunov_solve(&diag(&[2.0, 0.5]), &Matrix::eye(2)),
+            Err(MatrixError::Singular)
+        ));
+        assert!(matches!(
+            Matrix::lyapunov_solve(&a, &diag(&[1.0, -1.0, 1.0])),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            Matrix::lyapunov_solve(&a, &Matrix::from_string("1 2 0; 0 1 0; 0 0 1")),
+            Err(MatrixError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            Matrix::lyapunov_solve(&a, &Matrix::eye(2)),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_schur_eigenvalues() {
+        // Eigenvalues 1, 2 and 3 +- 4i are read off the blocks of T.
+        let t0 = Matrix::from_string("1 5 -2 7; 0 2 1 3; 0 0 3 -8; 0 0 2 3");
+        let q0 = Matrix::rotation_3d_axis(&[1.0, 2.0, 2.0], 0.7);
+        let mut q = Matrix::eye(4);
+        for i in 0..3 {
+            q[i][..3].copy_from_slice(&q0[i]);
+        }
+        let a = q.dot(&t0).dot(&q.transpose());
+        let (q, t) = a.schur().unwrap();
+        assert!(approx_eq(&q.dot(&t).dot(&q.transpose()), &a));
+
+        let mut real = Vec::new();
+        let mut pairs = Vec::new();
+        for b in t.schur_blocks() {
+            let i = b.start;
+            if b.len() == 1 {
+                real.push(t[i][i]);
+            } else {
+                let mu = 0.5 * (t[i][i] + t[i + 1][i + 1]);
+                let det = t[i][i] * t[i + 1][i + 1] - t[i][i + 1] * t[i + 1][i];
+                pairs.push((mu, (det - mu * mu).sqrt()));
+            }
+        }
+        real.sort_by(f64::total_cmp);
+        assert!((real[0] - 1.0).abs() < 1e-9 && (real[1] - 2.0).abs() < 1e-9);
+        assert_eq!(pairs.len(), 1);
+        assert!((pairs[0].0 - 3.0).abs() < 1e-9 && (pairs[0].1 - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_try_from_file() {
+        let path = std::env::temp_dir().join("linalg_test_try_from_file.txt");
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, "1 2\n3 x\n").unwrap();
+        assert!(matches!(
+            Matrix::try_from_file(path_str),
+            Err(MatrixError::ParseError(_))
+        ));
+        fs::write(&path, "1 2\n3\n").unwrap();
+        assert!(matches!(
+            Matrix::try_from_file(path_str),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+        fs::write(&path, "1 2\n3 4\n").unwrap();
+        assert_eq!(
+            Matrix::try_from_file(path_str).unwrap(),
+            Matrix::from_string("1 2; 3 4")
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            Matrix::try_from_file(path_str),
+            Err(MatrixError::Io { .. })
+        ));
     }
 }
@@ -0,0 +1,42 @@
+//! Conversions to and from single precision, for handing matrices to GPU
+//! APIs that work in `f32`. Narrowing rounds every entry to about 7
+//! significant digits and sends values beyond `f32::MAX` to infinity, so a
+//! round trip is not exact.
+
+use crate::{Matrix, MatrixError};
+
+impl Matrix {
+    /// The row-major entries rounded to `f32`.
+    pub fn to_f32_array(&self) -> Vec<f32> {
+        self.data.iter().map(|x| *x as f32).collect()
+    }
+
+    /// Builds a matrix from row-major `f32` data, widening each entry to `f64`.
+    /// Fails if `data` does not hold exactly `rows * cols` entries.
+    pub fn from_f32_array(rows: usize, cols: usize, data: Vec<f32>) -> Result<Self, MatrixError> {
+        Self::from_raw_parts(rows, cols, data.into_iter().map(f64::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f32_round_trip() {
+        let a = Matrix::from_string("1 0.5 -2; 3 0.1 1e300");
+        let narrow = a.to_f32_array();
+        assert_eq!(narrow[..4], [1.0, 0.5, -2.0, 3.0]);
+        assert!(narrow[5].is_infinite());
+
+        let b = Matrix::from_f32_array(2, 3, narrow).unwrap();
+        assert_eq!(b[0], [1.0, 0.5, -2.0]);
+        assert_ne!(b[1][1], 0.1);
+        assert!((b[1][1] - 0.1).abs() < 1e-7);
+
+        assert!(matches!(
+            Matrix::from_f32_array(2, 2, vec![1.0; 3]),
+            Err(MatrixError::DimensionMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Matrix, MatrixError};
+
+/// LU factorization with partial pivoting, `P A = L U`, stored compactly: `L`
+/// (unit diagonal omitted) below the diagonal of `lu` and `U` on and above it.
+pub(crate) struct Lu {
+    lu: Matrix,
+    /// `perm[i]` is the row of `A` that ended up in row `i`.
+    perm: Vec<usize>,
+    /// `det(P)`: `1.0` for an even number of row swaps, `-1.0` for odd.
+    sign: f64,
+}
+
+impl Lu {
+    pub(crate) fn new(a: &Matrix) -> Result<Self, MatrixError> {
+        if a.rows != a.cols {
+            return Err(MatrixError::NotSquare {
+                rows: a.rows,
+                cols: a.cols,
+            });
+        }
+        let n = a.rows;
+        let mut lu = a.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|x, y| lu[*x][k].abs().total_cmp(&lu[*y][k].abs()))
+                .unwrap();
+            if lu[p][k] == 0.0 {
+                return Err(MatrixError::Singular);
+            }
+            if p != k {
+                lu.swap_rows(k, p);
+                perm.swap(k, p);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let mult = lu[i][k] / lu[k][k];
+                lu[i][k] = mult;
+                for j in k + 1..n {
+                    lu[i][j] -= mult * lu[k][j];
+                }
+            }
+        }
+        Ok(Self { lu, perm, sign })
+    }
+
+    /// `det(A) = det(P) * prod(diag(U))`.
+    pub(crate) fn det(&self) -> f64 {
+        self.sign * self.lu.diag_prod()
+    }
+
+    /// `ln |det(A)|`, summed from the pivots so it cannot overflow or underflow.
+    pub(crate) fn log_abs_det(&self) -> f64 {
+        (0..self.lu.rows).map(|i| self.lu[i][i].abs().ln()).sum()
+    }
+
+    /// Solves `A X = B` for every column of `B`.
+    pub(crate) fn solve(&self, b: &Matrix) -> Result<Matrix, MatrixError> {
+        let n = self.lu.rows;
+        if b.rows != n {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Right-hand side has {} rows, expected {}.",
+                b.rows, n
+            )));
+        }
+        let mut x = Matrix::new(n, b.cols);
+        for (i, p) in self.perm.iter().enumerate() {
+            x[i].copy_from_slice(&b[*p]);
+        }
+        for c in 0..b.cols {
+            for i in 0..n {
+                for k in 0..i {
+                    x[i][c] -= self.lu[i][k] * x[k][c];
+                }
+            }
+            for i in (0..n).rev() {
+                for k in i + 1..n {
+                    x[i][c] -= self.lu[i][k] * x[k][c];
+                }
+                x[i][c] /= self.lu[i][i];
+            }
+        }
+        Ok(x)
+    }
+
+    /// Solves `A^T x = b` for a single right-hand side, reusing the factors of `A`.
+    pub(crate) fn solve_transpose(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.lu.rows;
+        let mut w = b.to_vec();
+        // U^T is lower triangular, L^T unit upper triangular.
+        for i in 0..n {
+            for k in 0..i {
+                w[i] -= self.lu[k][i] * w[k];
+            }
+            w[i] /= self.lu[i][i];
+        }
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                w[i] -= self.lu[k][i] * w[k];
+            }
+        }
+        let mut x = vec![0.0; n];
+        for (i, p) in self.perm.iter().enumerate() {
+            x[*p] = w[i];
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let a = Matrix::from_string("0 2 1 ; 1 1 1 ; 2 1 3");
+        let lu = Lu::new(&a).unwrap();
+        let x = lu.solve(&Matrix::from_string("7 ; 6 ; 13")).unwrap();
+
+        assert!(x
+            .data
+            .iter()
+            .zip([1.0, 2.0, 3.0])
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+        let y = lu.solve_transpose(&[7.0, 6.0, 13.0]);
+        let check = a.transpose().dot(&Matrix {
+            rows: 3,
+            cols: 1,
+            data: y,
+        });
+        assert!(check
+            .data
+            .iter()
+            .zip([7.0, 6.0, 13.0])
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+        assert_eq!(lu.det(), -3.0);
+        assert!(matches!(
+            Lu::new(&Matrix::from_string("1 2 ; 2 4")),
+            Err(MatrixError::Singular)
+        ));
+    }
+}
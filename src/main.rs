@@ -5,14 +5,14 @@ fn main() {
     // println!("{m1}");
     // let m2 = Matrix::from_string("7 8 9; 10 11 12");
     // println!("{m2}");
-    // let m3 = m1.combine(m2, |a, b| (a * b));
+    // let m3 = m1.zip_apply(&m2, |a, b| (a * b)).unwrap();
     // println!("{m3}");
 
     // let mut m: Matrix = Matrix::new(3,3);
     // m.identity();
 
-    // let mut mcpy = m.copy();
-    // mcpy.apply(|x| x+3.0);
+    // let mut mcpy = m.clone();
+    // mcpy.apply_inplace(|x| x+3.0);
     // println!("{mcpy}");
 
     // println!("{m}");
@@ -22,7 +22,7 @@ fn main() {
     // println!("det(m1) = {}", m1.det());
 
     // let mut m2 = Matrix::from_file("src/m1.txt");
-    // m2.apply(|i| i*2.0);
+    // m2.apply_inplace(|i| i*2.0);
     // m2.print();
     // println!("det(m2) = {}", m2.det());
 
@@ -34,14 +34,14 @@ fn main() {
     // m4.print();
     // println!("det(m4) = {}", m4.det());
 
-    // let m34 = m4.dot(m3);
+    // let m34 = m4.dot(&m3);
     // println!("m4 dot m3:");
     // m34.print();
 
     // let mut m4t = m4.transpose();
     // println!("testing apply");
     // m4t.print();
-    // m4t.apply(|x| x+99.0);
+    // m4t.apply_inplace(|x| x+99.0);
     // m4t.print();
 
     // let m5 = Matrix::from_string("3 0 2 ; 2 0 -2 ; 0 1 1 ");
@@ -71,5 +71,5 @@ fn main() {
 
     let m1 = Matrix::from_string("1 2 3; 4 5 6; 7 8 9");
     let m2 = Matrix::from_string("1; 2; 3");
-    m1.dot(m2).print();
+    m1.dot(&m2).print();
 }
@@ -0,0 +1,69 @@
+//! Conversions between `Matrix` and `ndarray::Array2<f64>`.
+//!
+//! Enabled with the `ndarray` feature.
+
+use crate::Matrix;
+use ndarray::Array2;
+
+impl From<Array2<f64>> for Matrix {
+    /// Builds a row-major `Matrix` from any `Array2`, regardless of its memory
+    /// order. Column-major (F-order), strided and sliced arrays are read in
+    /// logical row-major order, so the result always matches `arr[[i, j]]`.
+    /// The entries are always copied: the backing buffer of a sliced owned
+    /// array can hold elements outside its view.
+    fn from(arr: Array2<f64>) -> Self {
+        let (rows, cols) = arr.dim();
+        let data = arr.iter().copied().collect();
+        Self { rows, cols, data }
+    }
+}
+
+impl From<Matrix> for Array2<f64> {
+    /// Produces a C-order (row-major) `Array2` without copying the data.
+    fn from(m: Matrix) -> Self {
+        Array2::from_shape_vec((m.rows, m.cols), m.data)
+            .unwrap_or_else(|e| panic!("Matrix data does not match its dimensions: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{s, ShapeBuilder};
+
+    #[test]
+    fn test_from_c_order() {
+        let arr = Array2::from_shape_vec((2, 3), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let m = Matrix::from(arr);
+
+        assert_eq!(m, Matrix::from_string("1 2 3 ; 4 5 6"));
+    }
+
+    #[test]
+    fn test_from_f_order() {
+        let arr = Array2::from_shape_vec((2, 3).f(), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]).unwrap();
+        let m = Matrix::from(arr);
+
+        assert_eq!(m, Matrix::from_string("1 2 3 ; 4 5 6"));
+    }
+
+    #[test]
+    fn test_from_sliced() {
+        let mut arr = Array2::from_shape_vec((3, 3), (0..9).map(f64::from).collect()).unwrap();
+        arr.slice_collapse(s![1.., ..]);
+        let m = Matrix::from(arr);
+
+        assert_eq!(m, Matrix::from_string("3 4 5 ; 6 7 8"));
+        let back: Array2<f64> = m.into();
+        assert_eq!(back[[0, 0]], 3.0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let m = Matrix::from_string("1 2 ; 3 4 ; 5 6");
+        let arr: Array2<f64> = m.clone().into();
+
+        assert_eq!(arr[[2, 1]], 6.0);
+        assert_eq!(Matrix::from(arr), m);
+    }
+}
@@ -0,0 +1,313 @@
+//! Real Schur decomposition and the block helpers that matrix functions and
+//! the Sylvester solver use to work on quasi-triangular factors.
+
+use std::ops::Range;
+
+use crate::{householder_vector, Matrix, MatrixError};
+
+/// Francis steps allowed per eigenvalue before giving up.
+const MAX_SWEEPS: usize = 30;
+
+impl Matrix {
+    /// Real Schur decomposition `A = Q T Q^T` with `Q` orthogonal and `T`
+    /// quasi-upper-triangular, computed by Francis double-shift QR on the
+    /// Hessenberg form. Real eigenvalues appear as 1x1 blocks on the diagonal
+    /// of `T` and complex conjugate pairs as 2x2 blocks, whose eigenvalues are
+    /// the pair. Returns `NoConvergence` if an eigenvalue takes more than 30
+    /// sweeps to deflate.
+    pub fn schur(&self) -> Result<(Self, Self), MatrixError> {
+        let (mut q, mut h) = self.to_hessenberg()?;
+        let mut hi = h.rows;
+        let mut sweeps = 0;
+        let norm = h.norm();
+
+        while hi > 0 {
+            let mut lo = hi - 1;
+            while lo > 0 {
+                // Below eps ||A|| a subdiagonal entry is at the level of the
+                // rounding already committed, so dropping it is backward stable.
+                if h[lo][lo - 1].abs() <= f64::EPSILON * norm {
+                    h[lo][lo - 1] = 0.0;
+                    break;
+                }
+                lo -= 1;
+            }
+
+            match hi - lo {
+                1 => hi -= 1,
+                2 => {
+                    Self::split_real_pair(&mut h, &mut q, lo);
+                    hi -= 2;
+                }
+                _ => {
+                    sweeps += 1;
+                    if sweeps > MAX_SWEEPS {
+                        return Err(MatrixError::NoConvergence { iterations: sweeps });
+                    }
+                    Self::francis_step(&mut h, &mut q, lo, hi, sweeps);
+                    continue;
+                }
+            }
+            sweeps = 0;
+        }
+        Ok((q, h))
+    }
+
+    /// One implicit double-shift QR step on the active window `lo..hi`, using the
+    /// eigenvalues of the trailing 2x2 block as shifts. Every tenth sweep uses an
+    /// exceptional shift to break cycles.
+    fn francis_step(h: &mut Self, q: &mut Self, lo: usize, hi: usize, sweeps: usize) {
+        let m = hi - 1;
+        let (s, t) = if sweeps.is_multiple_of(10) {
+            let w = h[m][m - 1].abs() + h[m - 1][m - 2].abs();
+            (1.5 * w, w * w)
+        } else {
+            (
+                h[m - 1][m - 1] + h[m][m],
+                h[m - 1][m - 1] * h[m][m] - h[m - 1][m] * h[m][m - 1],
+            )
+        };
+
+        let mut x = h[lo][lo] * h[lo][lo] + h[lo][lo + 1] * h[lo + 1][lo] - s * h[lo][lo] + t;
+        let mut y = h[lo + 1][lo] * (h[lo][lo] + h[lo + 1][lo + 1] - s);
+        let mut z = h[lo + 1][lo] * h[lo + 2][lo + 1];
+        for k in lo..hi - 1 {
+            let bulge = if k + 2 < hi {
+                vec![x, y, z]
+            } else {
+                vec![x, y]
+            };
+            if let Some(v) = householder_vector(&bulge) {
+                h.reflect_rows(&v, k);
+                h.reflect_cols(&v, k);
+                q.reflect_cols(&v, k);
+            }
+            if k > lo {
+                for i in k + 1..(k + 3).min(hi) {
+                    h[i][k - 1] = 0.0;
+                }
+            }
+            if k + 2 < hi {
+                x = h[k + 1][k];
+                y = h[k + 2][k];
+                if k + 3 < hi {
+                    z = h[k + 3][k];
+                }
+            }
+        }
+    }
+
+    /// Splits the 2x2 block at `p` into two 1x1 blocks when its eigenvalues are
+    /// real, by reflecting an eigenvector onto `e_1`. Complex pairs are left as
+    /// a 2x2 block.
+    fn split_real_pair(h: &mut Self, q: &mut Self, p: usize) {
+        let (a, b, c, d) = (h[p][p], h[p][p + 1], h[p + 1][p], h[p + 1][p + 1]);
+        if c == 0.0 {
+            return;
+        }
+        let half = 0.5 * (a - d);
+        let disc = half * half + b * c;
+        if disc < 0.0 {
+            return;
+        }
+        let lambda = 0.5 * (a + d) + disc.sqrt().copysign(half);
+        let (u, w) = ([b, lambda - a], [lambda - d, c]);
+        let v = if u[0].hypot(u[1]) >= w[0].hypot(w[1]) {
+            u
+        } else {
+            w
+        };
+        if let Some(v) = householder_vector(&v) {
+            h.reflect_rows(&v, p);
+            h.reflect_cols(&v, p);
+            q.reflect_cols(&v, p);
+        }
+        h[p + 1][p] = 0.0;
+    }
+
+    /// Principal square root of a quasi-upper-triangular matrix, by Higham's
+    /// block recurrence `R_ii R_ij + R_ij R_jj = T_ij - sum_k R_ik R_kj`.
+    pub(crate) fn sqrt_quasi_triangular(&self) -> Result<Self, MatrixError> {
+        let blocks = self.schur_blocks();
+        let mut r = Self::new(self.rows, self.cols);
+        for (j, bj) in blocks.iter().enumerate() {
+            let root = Self::sqrt_block(&self.block(bj.clone(), bj.clone()))?;
+            r.set_block(bj.start, bj.start, &root);
+            for bi in blocks[..j].iter().rev() {
+                let mut rhs = self.block(bi.clone(), bj.clone());
+                for bk in blocks.iter().take(j).filter(|bk| bk.start > bi.start) {
+                    let p = r
+                        .block(bi.clone(), bk.clone())
+                        .dot(&r.block(bk.clone(), bj.clone()));
+                    rhs.data.iter_mut().zip(&p.data).for_each(|(x, y)| *x -= y);
+                }
+                let x = Self::small_sylvester(
+                    &r.block(bi.clone(), bi.clone()),
+                    &r.block(bj.clone(), bj.clone()),
+                    &rhs,
+                )?;
+                r.set_block(bi.start, bj.start, &x);
+            }
+        }
+        Ok(r)
+    }
+
+    /// Principal square root of a 1x1 block, or of a 2x2 block with complex
+    /// eigenvalues `mu +- i w`: with `s + i t` the root of `mu + i w`, it is
+    /// `s I + (B - mu I) / 2s`.
+    fn sqrt_block(b: &Self) -> Result<Self, MatrixError> {
+        if b.rows == 1 {
+            if b[0][0] < 0.0 {
+                return Err(MatrixError::InvalidParameter(
+                    "Matrix has a negative real eigenvalue, so it has no real square root."
+                        .to_string(),
+                ));
+            }
+            return Ok(b.apply(f64::sqrt));
+        }
+        let mu = 0.5 * (b[0][0] + b[1][1]);
+        let half = 0.5 * (b[0][0] - b[1][1]);
+        let w = (-(half * half + b[0][1] * b[1][0])).sqrt();
+        let modulus = mu.hypot(w);
+        let s = if mu >= 0.0 {
+            (0.5 * (modulus + mu)).sqrt()
+        } else {
+            w / (2.0 * (0.5 * (modulus - mu)).sqrt())
+        };
+        let mut root = b.apply(|x| x / (2.0 * s));
+        for i in 0..2 {
+            root[i][i] += s - mu / (2.0 * s);
+        }
+        Ok(root)
+    }
+
+    /// Diagonal block ranges of a quasi-upper-triangular matrix.
+    pub(crate) fn schur_blocks(&self) -> Vec<Range<usize>> {
+        let mut blocks = Vec::new();
+        let mut i = 0;
+        while i < self.rows {
+            let size = if i + 1 < self.rows && self[i + 1][i] != 0.0 {
+                2
+            } else {
+                1
+            };
+            blocks.push(i..i + size);
+            i += size;
+        }
+        blocks
+    }
+
+    /// Copy of the block with rows `r` and columns `c`.
+    pub(crate) fn block(&self, r: Range<usize>, c: Range<usize>) -> Self {
+        let mut m = Self::new(r.len(), c.len());
+        for (i, row) in r.enumerate() {
+            m[i].copy_from_slice(&self[row][c.clone()]);
+        }
+        m
+    }
+
+    /// Overwrites the block starting at `(r, c)` with `b`.
+    pub(crate) fn set_block(&mut self, r: usize, c: usize, b: &Self) {
+        for i in 0..b.rows {
+            self[r + i][c..c + b.cols].copy_from_slice(&b[i]);
+        }
+    }
+
+    /// Solves `A X + X B = C` for blocks of at most 2x2 through the Kronecker
+    /// form `(I ⊗ A + B^T ⊗ I) vec(X) = vec(C)`.
+    pub(crate) fn small_sylvester(a: &Self, b: &Self, c: &Self) -> Result<Self, MatrixError> {
+        let (p, q) = (a.rows, b.rows);
+        let mut k = Self::new(p * q, p * q);
+        for j in 0..q {
+            for i in 0..p {
+                for l in 0..p {
+                    k[j * p + i][j * p + l] += a[i][l];
+                }
+                for l in 0..q {
+                    k[j * p + i][l * p + i] += b[l][j];
+                }
+            }
+        }
+        let mut rhs = Self::new(p * q, 1);
+        for j in 0..q {
+            for i in 0..p {
+                rhs[j * p + i][0] = c[i][j];
+            }
+        }
+        let x = k.solve(&rhs)?;
+        let mut out = Self::new(p, q);
+        for j in 0..q {
+            for i in 0..p {
+                out[i][j] = x[j * p + i][0];
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(a: &Matrix) -> Matrix {
+        let (q, t) = a.schur().unwrap();
+        let n = a.rows;
+        let qtq = q.transpose().dot(&q);
+        let qtqt = q.dot(&t).dot(&q.transpose());
+        let scale = a.max_abs_element().max(1.0);
+        for i in 0..n {
+            for j in 0..n {
+                let id = if i == j { 1.0 } else { 0.0 };
+                assert!((qtq[i][j] - id).abs() < 1e-12);
+                assert!((qtqt[i][j] - a[i][j]).abs() < 1e-12 * scale);
+            }
+        }
+        for r in t.schur_blocks() {
+            if r.len() == 2 {
+                let (i, j) = (r.start, r.start + 1);
+                let half = 0.5 * (t[i][i] - t[j][j]);
+                assert!(half * half + t[i][j] * t[j][i] < 0.0);
+            }
+            assert!((r.end..n).all(|i| t[i][r.start..r.end].iter().all(|x| *x == 0.0)));
+        }
+        t
+    }
+
+    #[test]
+    fn test_schur() {
+        let t = check(&Matrix::from_string("4 1 2; 1 3 0; 2 0 1"));
+        assert_eq!(t.schur_blocks().len(), 3);
+
+        // A rotation keeps a complex pair.
+        let t = check(&Matrix::from_string("0 -1; 1 0"));
+        assert_eq!(t.schur_blocks(), vec![0..2]);
+
+        check(&Matrix::from_string(
+            "1 2 3 4 5; 6 7 8 9 1; 2 3 5 7 11; 0 1 0 1 0; 3 1 4 1 5",
+        ));
+        // Companion matrix of x^4 - 1: eigenvalues 1, -1 and i, -i.
+        let t = check(&Matrix::from_string("0 0 0 1; 1 0 0 0; 0 1 0 0; 0 0 1 0"));
+        assert_eq!(t.schur_blocks().len(), 3);
+        check(&Matrix::from_string("2 0; 0 2"));
+        check(&Matrix::new(0, 0));
+
+        assert!(matches!(
+            Matrix::new(2, 3).schur(),
+            Err(MatrixError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_small_sylvester() {
+        let a = Matrix::from_string("1 2; -1 3");
+        let b = Matrix::from_string("4 1; 0 2");
+        let x = Matrix::from_string("1 -1; 2 0.5");
+        let c = a.dot(&x).zip_apply(&x.dot(&b), |p, q| p + q).unwrap();
+        let y = Matrix::small_sylvester(&a, &b, &c).unwrap();
+        assert!(y
+            .data
+            .iter()
+            .zip(&x.data)
+            .all(|(p, q)| (p - q).abs() < 1e-12));
+    }
+}
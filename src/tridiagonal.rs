@@ -0,0 +1,135 @@
+use crate::{Matrix, MatrixError};
+
+/// An `n x n` tridiagonal matrix stored as its three diagonals, using `3n - 2`
+/// values instead of `n^2`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TridiagonalMatrix {
+    /// Subdiagonal, `lower[i]` is entry `(i + 1, i)`.
+    pub lower: Vec<f64>,
+    /// Main diagonal, `diag[i]` is entry `(i, i)`.
+    pub diag: Vec<f64>,
+    /// Superdiagonal, `upper[i]` is entry `(i, i + 1)`.
+    pub upper: Vec<f64>,
+}
+
+impl TridiagonalMatrix {
+    pub fn new(lower: Vec<f64>, diag: Vec<f64>, upper: Vec<f64>) -> Result<Self, MatrixError> {
+        let off = diag.len().saturating_sub(1);
+        if lower.len() != off || upper.len() != off {
+            return Err(MatrixError::DimensionMismatch(format!(
+                "Off-diagonals must have {} entries, got {} and {}.",
+                off,
+                lower.len(),
+                upper.len()
+            )));
+        }
+        Ok(Self { lower, diag, upper })
+    }
+
+    pub fn size(&self) -> usize {
+        self.diag.len()
+    }
+
+    pub fn to_dense(&self) -> Matrix {
+        let n = self.size();
+        let mut m = Matrix::new(n, n);
+        for i in 0..n {
+            m[i][i] = self.diag[i];
+            if i + 1 < n {
+                m[i + 1][i] = self.lower[i];
+                m[i][i + 1] = self.upper[i];
+            }
+        }
+        m
+    }
+
+    /// Solves `T x = b` in O(n) with the Thomas algorithm. There is no pivoting,
+    /// so this is meant for diagonally dominant or symmetric positive definite
+    /// systems; a zero pivot yields non-finite entries. Panics if `b` is not `n`
+    /// long.
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.size();
+        if b.len() != n {
+            panic!(
+                "Dimensions not matched. Right-hand side has {} entries, expected {}.",
+                b.len(),
+                n
+            );
+        }
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut c_prime = vec![0.0; n];
+        let mut d_prime = vec![0.0; n];
+        c_prime[0] = if n > 1 {
+            self.upper[0] / self.diag[0]
+        } else {
+            0.0
+        };
+        d_prime[0] = b[0] / self.diag[0];
+        for i in 1..n {
+            let denom = self.diag[i] - self.lower[i - 1] * c_prime[i - 1];
+            if i + 1 < n {
+                c_prime[i] = self.upper[i] / denom;
+            }
+            d_prime[i] = (b[i] - self.lower[i - 1] * d_prime[i - 1]) / denom;
+        }
+
+        let mut x = d_prime;
+        for i in (0..n - 1).rev() {
+            x[i] -= c_prime[i] * x[i + 1];
+        }
+        x
+    }
+}
+
+impl Matrix {
+    /// Tridiagonal view of `self` when it is square and every entry off the three
+    /// central diagonals is within `tol` of zero.
+    pub fn to_tridiagonal(&self, tol: f64) -> Option<TridiagonalMatrix> {
+        if self.rows != self.cols || !self.is_banded(1, 1, tol) {
+            return None;
+        }
+        let n = self.rows;
+        Some(TridiagonalMatrix {
+            lower: (1..n).map(|i| self[i][i - 1]).collect(),
+            diag: (0..n).map(|i| self[i][i]).collect(),
+            upper: (1..n).map(|i| self[i - 1][i]).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let m = Matrix::from_string("2 -1 0 ; -1 2 -1 ; 0 -1 2");
+        let t = m.to_tridiagonal(0.0).unwrap();
+
+        assert_eq!(t.lower, vec![-1.0, -1.0]);
+        assert_eq!(t.to_dense(), m);
+        assert!(Matrix::from_string("1 0 1 ; 0 1 0 ; 0 0 1")
+            .to_tridiagonal(0.0)
+            .is_none());
+        assert!(TridiagonalMatrix::new(vec![1.0], vec![1.0], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let t =
+            TridiagonalMatrix::new(vec![1.0, 1.0, 1.0], vec![4.0; 4], vec![2.0, 2.0, 2.0]).unwrap();
+        let x = t.solve(&[8.0, 15.0, 22.0, 19.0]);
+
+        for (xi, expected) in x.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+            assert!((xi - expected).abs() < 1e-12);
+        }
+        assert_eq!(
+            TridiagonalMatrix::new(vec![], vec![2.0], vec![])
+                .unwrap()
+                .solve(&[3.0]),
+            vec![1.5]
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use linalg::Matrix;
+use proptest::prelude::*;
+
+const TOL: f64 = 1e-6;
+
+fn square_matrix(n: usize) -> impl Strategy<Value = Matrix> {
+    prop::collection::vec(-10.0..10.0, n * n).prop_map(move |data| Matrix {
+        rows: n,
+        cols: n,
+        data,
+    })
+}
+
+/// Random square matrix made strictly diagonally dominant, so it is never
+/// singular.
+fn invertible_matrix(n: usize) -> impl Strategy<Value = Matrix> {
+    square_matrix(n).prop_map(move |mut m| {
+        for i in 0..n {
+            m[i][i] += 10.0 * n as f64;
+        }
+        m
+    })
+}
+
+fn pair(max: usize) -> impl Strategy<Value = (Matrix, Matrix)> {
+    (2..=max).prop_flat_map(|n| (square_matrix(n), square_matrix(n)))
+}
+
+fn approx_eq(a: &Matrix, b: &Matrix) -> bool {
+    a.rows == b.rows
+        && a.cols == b.cols
+        && a.data
+            .iter()
+            .zip(b.data.iter())
+            .all(|(x, y)| (x - y).abs() <= TOL * (1.0 + x.abs().max(y.abs())))
+}
+
+proptest! {
+    #[test]
+    fn transpose_of_product((a, b) in pair(6)) {
+        let lhs = a.dot(&b).transpose();
+        let rhs = b.transpose().dot(&a.transpose());
+        prop_assert!(approx_eq(&lhs, &rhs));
+    }
+
+    #[test]
+    fn det_of_product((a, b) in pair(4)) {
+        let lhs = a.dot(&b).det();
+        let rhs = a.det() * b.det();
+        prop_assert!((lhs - rhs).abs() <= TOL * (1.0 + lhs.abs().max(rhs.abs())));
+    }
+
+    #[test]
+    fn inverse_is_inverse(a in (2..=4usize).prop_flat_map(invertible_matrix)) {
+        let mut identity = Matrix::new(a.rows, a.cols);
+        identity.identity();
+        prop_assert!(approx_eq(&a.dot(&a.inverse()), &identity));
+    }
+
+    #[test]
+    fn trace_is_linear((a, b) in pair(6)) {
+        let lhs = a.zip_apply(&b, |x, y| x + y).unwrap().trace();
+        let rhs = a.trace() + b.trace();
+        prop_assert!((lhs - rhs).abs() <= TOL * (1.0 + lhs.abs()));
+    }
+
+    #[test]
+    fn transpose_is_involution(a in (1..=6usize).prop_flat_map(square_matrix)) {
+        prop_assert_eq!(a.transpose().transpose(), a);
+    }
+}